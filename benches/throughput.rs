@@ -0,0 +1,213 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+/*
+ * Throughput of strategies, with the standard library only.
+ *
+ * Run with `cargo bench`, optionally followed by `-- NAME...` to run only the benchmarks
+ * whose names contain any of the given words. Workloads are fixed, so that timings
+ * of different builds are comparable; each one is timed over several samples
+ * and the median time of a run is reported.
+ */
+
+use clarith::protocol::Reduction;
+use clarith::Number;
+use std::cmp::Ordering;
+use std::env;
+use std::hint::black_box;
+use std::iter;
+use std::time::{Duration, Instant};
+
+/// Samples timed per benchmark, after one run to warm up.
+const SAMPLES: usize = 11;
+
+/// Least duration of a sample.
+const SAMPLE: Duration = Duration::from_millis(10);
+
+/**
+ * Inputs drained per benchmark.
+ *
+ * Inputs of homographic and combine are kept short, since the coefficients
+ * of these strategies overflow on long irregular inputs.
+ */
+const BATCH: u64 = 64;
+
+fn main() {
+    let filters: Vec<String> = env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with('-'))
+        .collect();
+    let mut benchmarks: Vec<(String, Box<dyn Fn() -> usize>)> = vec![
+        ("ratio".to_string(), Box::new(ratio)),
+        ("homographic".to_string(), Box::new(homographic)),
+        ("combine".to_string(), Box::new(combine)),
+        ("compare".to_string(), Box::new(compare)),
+    ];
+    for depth in [1, 4, 16, 64].iter().cloned() {
+        benchmarks.push((
+            format!("nested_homographic/{}", depth),
+            Box::new(move || nested_homographic(depth)),
+        ));
+    }
+    for terms in [2, 8, 32, 128, 512].iter().cloned() {
+        benchmarks.push((format!("sum/{}", terms), Box::new(move || sum(terms))));
+    }
+    println!(
+        "{:<24} {:>12} {:>10} {:>14}",
+        "benchmark", "median", "symbols", "symbols/s"
+    );
+    for (name, run) in benchmarks.iter() {
+        if !filters.is_empty() && !filters.iter().any(|f| name.contains(f.as_str())) {
+            continue;
+        }
+        let (time, symbols) = measure(run);
+        println!(
+            "{:<24} {:>9.3} ms {:>10} {:>14.0}",
+            name,
+            time.as_secs_f64() * 1e3,
+            symbols,
+            symbols as f64 / time.as_secs_f64()
+        );
+    }
+}
+
+/// Median time of run, and the number of symbols it processes.
+fn measure(run: &dyn Fn() -> usize) -> (Duration, usize) {
+    // Repeat short runs within a sample, so that timer resolution does not matter.
+    let start = Instant::now();
+    let symbols = run();
+    let once = start.elapsed().as_nanos().max(1);
+    let repeat = (SAMPLE.as_nanos() / once).max(1) as u32;
+    let mut times: Vec<Duration> = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..repeat {
+                black_box(run());
+            }
+            start.elapsed() / repeat
+        })
+        .collect();
+    times.sort();
+    (times[SAMPLES / 2], symbols)
+}
+
+/// Egest every symbol of x, returning how many there are.
+fn drain(x: Number) -> usize {
+    match x {
+        Number::Special(_) => 0,
+        Number::Other(_, clog) => clog.map(black_box).count(),
+    }
+}
+
+/// An input of len pseudorandom symbols given by seed.
+fn stream(seed: u64, len: usize) -> Number {
+    let mut state = seed;
+    let symbols = iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        if state.is_multiple_of(3) {
+            Reduction::Amplify
+        } else {
+            Reduction::Uncover
+        }
+    });
+    Number::from_symbols(None, symbols.take(len))
+}
+
+/// Symbols of ratios with large terms.
+fn ratio() -> usize {
+    (0..256)
+        .map(|k| drain(Number::ratio(isize::MAX - 2 * k, isize::MAX / 3 + k)))
+        .sum()
+}
+
+/// Symbols of a homographic function of inputs.
+fn homographic() -> usize {
+    (1..=BATCH)
+        .map(|seed| drain(Number::homographic(stream(seed, 64), 3, 1, 2, 5)))
+        .sum()
+}
+
+/// Symbols of the sum of pairs of inputs.
+fn combine() -> usize {
+    (1..=BATCH)
+        .map(|seed| {
+            drain(Number::combine(
+                stream(seed, 24),
+                stream(seed + BATCH, 24),
+                0,
+                1,
+                1,
+                0,
+                0,
+                0,
+                0,
+                1,
+            ))
+        })
+        .sum()
+}
+
+/// Symbols compared between pairs of long inputs that differ only at the end.
+fn compare() -> usize {
+    (1..=BATCH)
+        .map(|seed| {
+            let mut other: Vec<Reduction> = stream(seed, 4096).unwrap_other().1.collect();
+            let last = other.pop();
+            other.push(match last {
+                Some(Reduction::Amplify) => Reduction::Uncover,
+                _ => Reduction::Amplify,
+            });
+            let len = other.len();
+            assert_ne!(
+                Number::compare(stream(seed, 4096), Number::from_symbols(None, other)),
+                Ordering::Equal
+            );
+            len
+        })
+        .sum()
+}
+
+/// Symbols of chains of homographic functions of inputs.
+fn nested_homographic(depth: usize) -> usize {
+    (1..=BATCH / 4)
+        .map(|seed| {
+            drain((0..depth).fold(stream(seed, 48), |x, k| {
+                if k % 2 == 0 {
+                    Number::homographic(x, 1, 1, 1, 2)
+                } else {
+                    Number::homographic(x, 2, 1, 1, 1)
+                }
+            }))
+        })
+        .sum()
+}
+
+/**
+ * Symbols of the sum of ratios, added pairwise in a balanced tree.
+ *
+ * Denominators are few, so that the sum fits the coefficients however many terms there are.
+ */
+fn sum(terms: usize) -> usize {
+    drain(Number::sum(
+        (0..terms).map(|k| Number::ratio(k as isize % 4 + 1, [3, 5, 6][k % 3])),
+    ))
+}
@@ -0,0 +1,135 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::Number;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq(sum(Vec::new()), Number::ratio(0, 1));
+        assert_eq(product(Vec::new()), Number::ratio(1, 1));
+    }
+
+    #[test]
+    fn single() {
+        assert_eq(sum(vec![Number::ratio(-2, 3)]), Number::ratio(-2, 3));
+        assert_eq(product(vec![Number::ratio(-2, 3)]), Number::ratio(-2, 3));
+    }
+
+    #[test]
+    fn sum_odd_count() {
+        assert_eq(
+            sum(vec![
+                Number::ratio(1, 2),
+                Number::ratio(-1, 3),
+                Number::ratio(1, 4),
+                Number::ratio(2, 1),
+                Number::ratio(-1, 1),
+            ]),
+            Number::ratio(17, 12),
+        );
+    }
+
+    #[test]
+    fn product_odd_count() {
+        assert_eq(
+            product(vec![
+                Number::ratio(1, 2),
+                Number::ratio(-1, 3),
+                Number::ratio(3, 4),
+                Number::ratio(2, 1),
+                Number::ratio(-1, 1),
+            ]),
+            Number::ratio(1, 4),
+        );
+    }
+
+    #[test]
+    fn sum_many() {
+        assert_eq(
+            sum((0..1000).map(|k| Number::ratio(k % 3 - 1, 2))),
+            Number::ratio(-1, 2),
+        );
+    }
+
+    #[test]
+    fn sum_telescoping() {
+        let n = 16;
+        assert_eq(
+            sum((1..=n).map(|k| Number::ratio(1, k * (k + 1)))),
+            Number::ratio(n, n + 1),
+        );
+    }
+
+    #[test]
+    fn product_telescoping() {
+        let n = 128;
+        assert_eq(
+            product((1..=n).map(|k| Number::ratio(k + 1, k))),
+            Number::ratio(n + 1, 1),
+        );
+    }
+}
+
+pub fn sum<I: IntoIterator<Item = Number>>(terms: I) -> Number {
+    fold(terms, Number::ratio(0, 1), |x, y| {
+        Number::combine(x, y, 0, 1, 1, 0, 0, 0, 0, 1)
+    })
+}
+
+pub fn product<I: IntoIterator<Item = Number>>(factors: I) -> Number {
+    fold(factors, Number::ratio(1, 1), |x, y| {
+        Number::combine(x, y, 1, 0, 0, 0, 0, 0, 0, 1)
+    })
+}
+
+/// Reduce operands pairwise so that the evaluation tree has logarithmic depth.
+fn fold<I, F>(operands: I, identity: Number, op: F) -> Number
+where
+    I: IntoIterator<Item = Number>,
+    F: Fn(Number, Number) -> Number,
+{
+    let mut level: Vec<Number> = operands.into_iter().collect();
+    if level.is_empty() {
+        return identity;
+    }
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity(level.len().div_ceil(2));
+        let mut operands = level.into_iter();
+        while let Some(x) = operands.next() {
+            next.push(match operands.next() {
+                Some(y) => op(x, y),
+                None => x,
+            });
+        }
+        level = next;
+    }
+    level.pop().unwrap()
+}
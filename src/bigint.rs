@@ -0,0 +1,447 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Shl, Shr, Sub};
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn big(value: i128) -> BigInt {
+        BigInt::from(value)
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(big(0).to_string(), "0");
+        assert_eq!(big(-7).to_string(), "-7");
+        assert_eq!(big(i128::MAX).to_string(), i128::MAX.to_string());
+        assert_eq!(big(i128::MIN).to_string(), i128::MIN.to_string());
+        assert_eq!(format!("{:?}", big(-1_000_000_000)), "-1000000000");
+    }
+
+    #[test]
+    fn arithmetic() {
+        let values = [
+            0,
+            1,
+            -1,
+            7,
+            -12,
+            1 << 32,
+            -(1 << 40) + 3,
+            isize::MAX as i128,
+            isize::MIN as i128,
+        ];
+        for a in values.iter() {
+            for b in values.iter() {
+                assert_eq!(&big(*a) + &big(*b), big(a + b));
+                assert_eq!(&big(*a) - &big(*b), big(a - b));
+                assert_eq!(&big(*a) * &big(*b), big(a * b));
+                assert_eq!(big(*a).cmp(&big(*b)), a.cmp(b));
+                if *b != 0 {
+                    // Rust rounds quotients towards zero.
+                    let q = if a % b != 0 && (*a < 0) != (*b < 0) {
+                        a / b - 1
+                    } else {
+                        a / b
+                    };
+                    assert_eq!(big(*a).div_mod_floor(&big(*b)), (big(q), big(a - b * q)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn large() {
+        let a = &big(1 << 100) * &big(1 << 100);
+        assert_eq!(
+            a.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(&(&a - &big(1)) + &big(1), a);
+        assert_eq!(a.div_mod_floor(&big(1 << 100)), (big(1 << 100), big(0)));
+        let (q, r) = (&a + &big(5)).div_mod_floor(&big(-3));
+        assert_eq!(&(&q * &big(-3)) + &r, &a + &big(5));
+        assert!(r <= big(0) && r > big(-3));
+        assert_eq!(&a >> 199, big(2));
+        assert_eq!(&big(3) << 70, big(3 << 70));
+        assert_eq!(a.trailing_zeros(), Some(200));
+        assert_eq!(big(1 << 100).pow(2), a);
+        assert_eq!(big(-3).pow(5), big(-243));
+        assert_eq!(big(0).pow(0), big(1));
+        assert_eq!(big(0).trailing_zeros(), None);
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(BigInt::from(-5isize).to_isize(), Some(-5));
+        assert_eq!(BigInt::from(isize::MIN).to_isize(), Some(isize::MIN));
+        assert_eq!(big(isize::MAX as i128 + 1).to_isize(), None);
+        assert_eq!(big(-3).signum(), -1);
+        assert_eq!(big(0).signum(), 0);
+        assert!(big(10).is_even() && !big(-7).is_even());
+    }
+}
+
+/// A signed integer of unbounded size.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Magnitude as little endian 32 bit limbs, without trailing zeros.
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    /// The value as isize, if it fits.
+    pub fn to_isize(&self) -> Option<isize> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u128, |m, limb| (m << 32) | u128::from(*limb));
+        if self.negative {
+            if magnitude <= isize::MIN.unsigned_abs() as u128 {
+                Some((magnitude as isize).wrapping_neg())
+            } else {
+                None
+            }
+        } else if magnitude <= isize::MAX as u128 {
+            Some(magnitude as isize)
+        } else {
+            None
+        }
+    }
+
+    /// -1, 0 or 1 as self is negative, zero or positive.
+    pub fn signum(&self) -> isize {
+        if self.limbs.is_empty() {
+            0
+        } else if self.negative {
+            -1
+        } else {
+            1
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.limbs.first().is_none_or(|limb| limb % 2 == 0)
+    }
+
+    /// The exponent of the largest power of two dividing self, None for zero.
+    pub fn trailing_zeros(&self) -> Option<usize> {
+        let i = self.limbs.iter().position(|limb| *limb != 0)?;
+        Some(32 * i + self.limbs[i].trailing_zeros() as usize)
+    }
+
+    /// Self raised to the power exp.
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut power = BigInt::from(1isize);
+        while exp > 0 {
+            if !exp.is_multiple_of(2) {
+                power = &power * &base;
+            }
+            exp /= 2;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        power
+    }
+
+    /**
+     * Quotient rounded towards minus infinity, and the remainder,
+     * which takes the sign of the divisor.
+     *
+     * Panics if the divisor is zero.
+     */
+    pub fn div_mod_floor(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        if divisor.is_zero() {
+            panic!("division by zero");
+        }
+        let (q, r) = div_rem_limbs(&self.limbs, &divisor.limbs);
+        let mut q = BigInt::new(self.negative != divisor.negative, q);
+        let mut r = BigInt::new(self.negative, r);
+        if !r.is_zero() && r.negative != divisor.negative {
+            q = &q - &BigInt::from(1isize);
+            r = &r + divisor;
+        }
+        (q, r)
+    }
+
+    fn magnitude_cmp(&self, other: &BigInt) -> Ordering {
+        compare_limbs(&self.limbs, &other.limbs)
+    }
+}
+
+fn compare_limbs(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let s = u64::from(*a.get(i).unwrap_or(&0)) + u64::from(*b.get(i).unwrap_or(&0)) + carry;
+        sum.push(s as u32);
+        carry = s >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// a - b, for a not less than b.
+fn sub_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, x) in a.iter().enumerate() {
+        let s = i64::from(*x) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+        difference.push(s.rem_euclid(1 << 32) as u32);
+        borrow = if s < 0 { 1 } else { 0 };
+    }
+    difference
+}
+
+fn mul_limbs(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, y) in b.iter().enumerate() {
+            let p = u64::from(*x) * u64::from(*y) + u64::from(product[i + j]) + carry;
+            product[i + j] = p as u32;
+            carry = p >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    product
+}
+
+/// Quotient and remainder of magnitudes, by long division one bit at a time.
+fn div_rem_limbs(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..32 * a.len()).rev() {
+        // remainder = 2 * remainder + next bit of a
+        remainder = shl_limbs(&remainder, 1);
+        if a[bit / 32] & (1 << (bit % 32)) != 0 {
+            if remainder.is_empty() {
+                remainder.push(1);
+            } else {
+                remainder[0] |= 1;
+            }
+        }
+        if compare_limbs(&remainder, b) != Ordering::Less {
+            remainder = trim(sub_limbs(&remainder, b));
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+fn shl_limbs(a: &[u32], bits: usize) -> Vec<u32> {
+    let (words, bits) = (bits / 32, bits % 32);
+    let mut shifted = vec![0; words];
+    let mut carry = 0;
+    for limb in a {
+        let wide = (u64::from(*limb) << bits) | carry;
+        shifted.push(wide as u32);
+        carry = wide >> 32;
+    }
+    shifted.push(carry as u32);
+    trim(shifted)
+}
+
+impl From<isize> for BigInt {
+    fn from(value: isize) -> BigInt {
+        BigInt::from(value as i128)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> BigInt {
+        let mut magnitude = value.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude != 0 {
+            limbs.push(magnitude as u32);
+            magnitude >>= 32;
+        }
+        BigInt::new(value < 0, limbs)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_limbs(&self.limbs, &other.limbs));
+        }
+        // Subtract the smaller magnitude from the larger one, which gives the sign.
+        match self.magnitude_cmp(other) {
+            Ordering::Less => BigInt::new(other.negative, sub_limbs(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_limbs(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_limbs(&self.limbs, &other.limbs),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs)
+    }
+}
+
+impl Shl<usize> for &BigInt {
+    type Output = BigInt;
+
+    /// Multiplication by a power of two.
+    fn shl(self, bits: usize) -> BigInt {
+        BigInt::new(self.negative, shl_limbs(&self.limbs, bits))
+    }
+}
+
+impl Shr<usize> for &BigInt {
+    type Output = BigInt;
+
+    /// Division by a power of two, rounding the magnitude down.
+    fn shr(self, bits: usize) -> BigInt {
+        let (words, bits) = (bits / 32, bits % 32);
+        let limbs = self.limbs.get(words..).unwrap_or(&[]);
+        let shifted = (0..limbs.len())
+            .map(|i| {
+                let high = u64::from(*limbs.get(i + 1).unwrap_or(&0)) << 32;
+                ((high | u64::from(limbs[i])) >> bits) as u32
+            })
+            .collect();
+        BigInt::new(self.negative, shifted)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => self.magnitude_cmp(other),
+            (true, true) => other.magnitude_cmp(self),
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Peel off decimal digits nine at a time by long division.
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            let mut remainder = 0u64;
+            for limb in limbs.iter_mut().rev() {
+                let n = (remainder << 32) | u64::from(*limb);
+                *limb = (n / 1_000_000_000) as u32;
+                remainder = n % 1_000_000_000;
+            }
+            chunks.push(remainder);
+            limbs = trim(limbs);
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
@@ -24,6 +24,7 @@ use crate::Number;
 use std::cmp::Ordering;
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 
     use super::*;
@@ -256,6 +257,7 @@ pub fn compare(n1: Number, n2: Number) -> Ordering {
     }
 }
 
+#[allow(clippy::needless_return)]
 fn compare_specials(s1: protocol::Special, s2: protocol::Special) -> Ordering {
     if s1 == s2 {
         return Ordering::Equal;
@@ -272,6 +274,7 @@ fn compare_specials(s1: protocol::Special, s2: protocol::Special) -> Ordering {
     return Ordering::Greater;
 }
 
+#[allow(clippy::needless_return, clippy::redundant_pattern_matching)]
 fn compare_hybrid(s: protocol::Special, p: Option<protocol::Primer>) -> Ordering {
     if let Some(protocol::Primer::Ground) = p {
         return Ordering::Greater;
@@ -304,6 +307,7 @@ fn compare_others(
     ))
 }
 
+#[allow(clippy::needless_return, clippy::redundant_pattern_matching)]
 fn compare_primers(p1: &Option<protocol::Primer>, p2: &Option<protocol::Primer>) -> Ordering {
     if p1 == p2 {
         return Ordering::Equal;
@@ -0,0 +1,455 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+/*!
+ * Lazy arithmetic expressions over Numbers.
+ *
+ * An expression graph is built from Number leaves and evaluated into a single Number.
+ * On evaluation:
+ *  - nodes reached through several paths (either the same Expr cloned,
+ *    or structurally identical subexpressions) are computed once and forked;
+ *  - chains of homographic steps, and homographic steps feeding or fed by a combine,
+ *    are fused into a single set of coefficients.
+ */
+
+use crate::strategy::support::{self, dot};
+use crate::Number;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn assert_eq(e: Expr, n: Number) {
+        assert_eq!(Number::compare(e.eval(), n), Ordering::Equal);
+    }
+
+    #[test]
+    fn leaves() {
+        assert_eq(Expr::var(Number::ratio(-2, 3)), Number::ratio(-2, 3));
+        assert_eq(Expr::ratio(5, -10), Number::ratio(-1, 2));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let x = Expr::var(Number::ratio(2, 3));
+        let y = Expr::var(Number::ratio(-1, 4));
+        let e = Expr::div(
+            &Expr::add(&Expr::mul(&x, &y), &Expr::ratio(1, 1)),
+            &Expr::sub(&x, &y),
+        );
+        // (2/3 * -1/4 + 1) / (2/3 + 1/4) = (5/6) / (11/12)
+        assert_eq(e, Number::ratio(10, 11));
+    }
+
+    #[test]
+    fn constants_fold() {
+        let e = Expr::recip(&Expr::add(
+            &Expr::mul(&Expr::ratio(2, 3), &Expr::ratio(3, 4)),
+            &Expr::neg(&Expr::ratio(1, 4)),
+        ));
+        assert_eq(e, Number::ratio(4, 1));
+    }
+
+    #[test]
+    fn shared_variable() {
+        let x = Expr::var(Number::ratio(3, 7));
+        // x * x + x
+        let e = Expr::add(&Expr::mul(&x, &x), &x);
+        assert_eq(e, Number::ratio(30, 49));
+    }
+
+    #[test]
+    fn shared_subexpression() {
+        let x = Expr::var(Number::ratio(3, 7));
+        let y = Expr::var(Number::ratio(-2, 5));
+        // (x + y) * (x + y) built twice, and (x + y) / (x + y)
+        let s1 = Expr::add(&x, &y);
+        let s2 = Expr::add(&x, &y);
+        let e = Expr::mul(&s1, &s2);
+        let mut graph = Graph::new();
+        graph.visit(&e);
+        assert_eq!(graph.uses[graph.id(&s1)], 2);
+        assert_eq!(graph.id(&s1), graph.id(&s2));
+        assert_eq(e, Number::ratio(1, 1225));
+        let x = Expr::var(Number::ratio(3, 7));
+        let y = Expr::var(Number::ratio(-2, 5));
+        let s = Expr::add(&x, &y);
+        assert_eq(Expr::div(&s, &s), Number::ratio(1, 1));
+    }
+
+    #[test]
+    fn fused_chain() {
+        let x = Expr::var(Number::ratio(1, 3));
+        let mut e = x;
+        for _ in 0..20 {
+            // e = 1 / (e + 1)
+            e = Expr::recip(&Expr::add(&e, &Expr::ratio(1, 1)));
+        }
+        assert_eq(e, Number::ratio(6765 * 3 + 4181, 10946 * 3 + 6765));
+    }
+
+    #[test]
+    fn fused_into_combine() {
+        let x = Expr::var(Number::ratio(1, 3));
+        let y = Expr::var(Number::ratio(-1, 2));
+        // 2 / (3 * (x + 1) * (1 / y) - 1)
+        let e = Expr::homographic(
+            &Expr::mul(&Expr::homographic(&x, 3, 3, 0, 1), &Expr::recip(&y)),
+            0,
+            2,
+            1,
+            -1,
+        );
+        assert_eq(e, Number::ratio(-2, 9));
+    }
+
+    #[test]
+    #[should_panic(expected = "variable already evaluated")]
+    fn forbids_reevaluation() {
+        let x = Expr::var(Number::ratio(1, 3));
+        let y = Expr::neg(&x);
+        x.eval();
+        y.eval();
+    }
+}
+
+/// A node of a lazy expression graph. Cloning an Expr shares the node.
+#[derive(Clone)]
+pub struct Expr(Rc<Node>);
+
+enum Node {
+    Var(RefCell<Option<Number>>),
+    Ratio(isize, isize),
+    Homographic(Expr, [isize; 4]),
+    Combine(Expr, Expr, [isize; 8]),
+}
+
+const IDENTITY: [isize; 4] = [1, 0, 0, 1];
+
+impl Expr {
+    /// A leaf holding an already constructed Number.
+    pub fn var(x: Number) -> Expr {
+        Expr(Rc::new(Node::Var(RefCell::new(Some(x)))))
+    }
+
+    /// A leaf holding the ratio of two signed machine integers.
+    pub fn ratio(num: isize, den: isize) -> Expr {
+        Expr(Rc::new(Node::Ratio(num, den)))
+    }
+
+    /// The expression _(nx * x + n) / (dx * x + d)_.
+    pub fn homographic(x: &Expr, nx: isize, n: isize, dx: isize, d: isize) -> Expr {
+        Expr(Rc::new(Node::Homographic(x.clone(), [nx, n, dx, d])))
+    }
+
+    /// The expression _(nxy * x * y + nx * x + ny * y + n) / (dxy * x * y + dx * x + dy * y + d)_.
+    #[allow(clippy::too_many_arguments)]
+    pub fn combine(
+        x: &Expr,
+        y: &Expr,
+        nxy: isize,
+        nx: isize,
+        ny: isize,
+        n: isize,
+        dxy: isize,
+        dx: isize,
+        dy: isize,
+        d: isize,
+    ) -> Expr {
+        Expr(Rc::new(Node::Combine(
+            x.clone(),
+            y.clone(),
+            [nxy, nx, ny, n, dxy, dx, dy, d],
+        )))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn neg(x: &Expr) -> Expr {
+        Expr::homographic(x, -1, 0, 0, 1)
+    }
+
+    pub fn recip(x: &Expr) -> Expr {
+        Expr::homographic(x, 0, 1, 1, 0)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(x: &Expr, y: &Expr) -> Expr {
+        Expr::combine(x, y, 0, 1, 1, 0, 0, 0, 0, 1)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn sub(x: &Expr, y: &Expr) -> Expr {
+        Expr::combine(x, y, 0, 1, -1, 0, 0, 0, 0, 1)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(x: &Expr, y: &Expr) -> Expr {
+        Expr::combine(x, y, 1, 0, 0, 0, 0, 0, 0, 1)
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn div(x: &Expr, y: &Expr) -> Expr {
+        Expr::combine(x, y, 0, 1, 0, 0, 0, 0, 1, 0)
+    }
+
+    /**
+     * Evaluate the expression into a Number.
+     *
+     * Variables are consumed: evaluating another expression that shares a variable
+     * with an already evaluated one panics.
+     */
+    pub fn eval(self) -> Number {
+        let mut graph = Graph::new();
+        graph.visit(&self);
+        graph.eval(&self, IDENTITY)
+    }
+
+    fn address(&self) -> usize {
+        Rc::as_ptr(&self.0) as usize
+    }
+}
+
+/// Structural identity of a node, given the identities of its children.
+#[derive(PartialEq, Eq, Hash)]
+enum Key {
+    Var(usize),
+    Ratio(isize, isize),
+    Homographic(usize, [isize; 4]),
+    Combine(usize, usize, [isize; 8]),
+}
+
+/// Evaluation state: canonical node identities, use counts and forked values of shared nodes.
+struct Graph {
+    ids: HashMap<usize, usize>,
+    keys: HashMap<Key, usize>,
+    uses: Vec<usize>,
+    values: HashMap<usize, Vec<Number>>,
+}
+
+impl Graph {
+    fn new() -> Graph {
+        Graph {
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+            uses: Vec::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    /// Count one more use of a node, visiting its children on the first use.
+    fn visit(&mut self, e: &Expr) -> usize {
+        if let Some(&id) = self.ids.get(&e.address()) {
+            self.uses[id] += 1;
+            return id;
+        }
+        let key = match &*e.0 {
+            Node::Var(_) => Key::Var(e.address()),
+            Node::Ratio(num, den) => {
+                let (num, den) = reduce_ratio(*num, *den);
+                Key::Ratio(num, den)
+            }
+            Node::Homographic(x, m) => Key::Homographic(self.visit(x), *m),
+            Node::Combine(x, y, c) => {
+                let x = self.visit(x);
+                let y = self.visit(y);
+                Key::Combine(x, y, *c)
+            }
+        };
+        let id = match self.keys.get(&key) {
+            Some(&id) => {
+                // A structural duplicate: its children were counted once too many.
+                match &*e.0 {
+                    Node::Homographic(x, _) => self.release(x),
+                    Node::Combine(x, y, _) => {
+                        self.release(x);
+                        self.release(y);
+                    }
+                    _ => {}
+                }
+                self.uses[id] += 1;
+                id
+            }
+            None => {
+                let id = self.uses.len();
+                self.uses.push(1);
+                self.keys.insert(key, id);
+                id
+            }
+        };
+        self.ids.insert(e.address(), id);
+        id
+    }
+
+    fn release(&mut self, e: &Expr) {
+        let id = self.ids[&e.address()];
+        self.uses[id] -= 1;
+    }
+
+    fn id(&self, e: &Expr) -> usize {
+        self.ids[&e.address()]
+    }
+
+    /// Ratio leaves are cheap to rebuild and are never shared, so that they can be folded.
+    fn is_shared(&self, e: &Expr) -> bool {
+        match &*e.0 {
+            Node::Ratio(_, _) => false,
+            _ => self.uses[self.id(e)] > 1,
+        }
+    }
+
+    /// The Number _m(e)_, where _m_ is a homographic transformation.
+    fn eval(&mut self, e: &Expr, m: [isize; 4]) -> Number {
+        if self.is_shared(e) {
+            let x = self.shared_value(e);
+            return homographic(x, m);
+        }
+        match &*e.0 {
+            Node::Var(x) => homographic(take(x), m),
+            Node::Ratio(num, den) => {
+                let (num, den) = apply_ratio(m, *num, *den);
+                Number::ratio(num, den)
+            }
+            Node::Homographic(x, m2) => self.eval(x, support::compose(m, *m2)),
+            Node::Combine(x, y, c) => self.eval_combine(x, y, combine_output(m, *c)),
+        }
+    }
+
+    fn eval_combine(&mut self, x: &Expr, y: &Expr, mut c: [isize; 8]) -> Number {
+        let (x, y) = {
+            let mut x = x;
+            while let (false, Node::Homographic(inner, m)) = (self.is_shared(x), &*x.0) {
+                c = support::substitute_x(c, *m);
+                x = inner;
+            }
+            let mut y = y;
+            while let (false, Node::Homographic(inner, m)) = (self.is_shared(y), &*y.0) {
+                c = support::substitute_y(c, *m);
+                y = inner;
+            }
+            (x, y)
+        };
+        if let Node::Ratio(p, q) = &*x.0 {
+            if !self.is_shared(x) {
+                let m = fix_x(c, *p, *q);
+                return self.eval(y, m);
+            }
+        }
+        if let Node::Ratio(p, q) = &*y.0 {
+            if !self.is_shared(y) {
+                let m = fix_y(c, *p, *q);
+                return self.eval(x, m);
+            }
+        }
+        let xv = self.eval(x, IDENTITY);
+        let yv = self.eval(y, IDENTITY);
+        let c = reduced(c);
+        Number::combine(xv, yv, c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7])
+    }
+
+    /// One of the forked values of a shared node, computing it on first demand.
+    fn shared_value(&mut self, e: &Expr) -> Number {
+        let id = self.id(e);
+        if !self.values.contains_key(&id) {
+            let uses = self.uses[id];
+            // Evaluate the node body as if it had a single use.
+            self.uses[id] = 1;
+            let mut x = self.eval(e, IDENTITY);
+            self.uses[id] = uses;
+            let mut forks = Vec::with_capacity(uses);
+            for _ in 1..uses {
+                let (x1, x2) = Number::fork(x);
+                forks.push(x1);
+                x = x2;
+            }
+            forks.push(x);
+            self.values.insert(id, forks);
+        }
+        self.values.get_mut(&id).unwrap().pop().unwrap()
+    }
+}
+
+fn take(x: &RefCell<Option<Number>>) -> Number {
+    match x.borrow_mut().take() {
+        Some(x) => x,
+        None => panic!("variable already evaluated"),
+    }
+}
+
+fn homographic(x: Number, m: [isize; 4]) -> Number {
+    if m == IDENTITY {
+        return x;
+    }
+    let m = reduced(m);
+    Number::homographic(x, m[0], m[1], m[2], m[3])
+}
+
+fn apply_ratio(m: [isize; 4], p: isize, q: isize) -> (isize, isize) {
+    reduce_ratio(dot(m[0], p, m[1], q), dot(m[2], p, m[3], q))
+}
+
+/// Apply _m_ to the output of a combine.
+fn combine_output(m: [isize; 4], c: [isize; 8]) -> [isize; 8] {
+    let mut r = [0; 8];
+    for i in 0..4 {
+        r[i] = dot(m[0], c[i], m[1], c[i + 4]);
+        r[i + 4] = dot(m[2], c[i], m[3], c[i + 4]);
+    }
+    reduced(r)
+}
+
+/// The homographic transformation of _y_ left by fixing the _x_ input of a combine at _p / q_.
+fn fix_x(c: [isize; 8], p: isize, q: isize) -> [isize; 4] {
+    reduced([
+        dot(c[0], p, c[2], q),
+        dot(c[1], p, c[3], q),
+        dot(c[4], p, c[6], q),
+        dot(c[5], p, c[7], q),
+    ])
+}
+
+/// The homographic transformation of _x_ left by fixing the _y_ input of a combine at _p / q_.
+fn fix_y(c: [isize; 8], p: isize, q: isize) -> [isize; 4] {
+    reduced([
+        dot(c[0], p, c[1], q),
+        dot(c[2], p, c[3], q),
+        dot(c[4], p, c[5], q),
+        dot(c[6], p, c[7], q),
+    ])
+}
+
+/// The coefficients divided by their greatest common divisor.
+fn reduced<const N: usize>(mut c: [isize; N]) -> [isize; N] {
+    support::reduce(&mut c);
+    c
+}
+
+fn reduce_ratio(num: isize, den: isize) -> (isize, isize) {
+    let [num, den] = reduced([num, den]);
+    if den < 0 {
+        (-num, -den)
+    } else {
+        (num, den)
+    }
+}
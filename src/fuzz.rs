@@ -0,0 +1,431 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+//! Fuzz targets, run by cargo-fuzz from the `fuzz` directory
+//! and as unit tests over its seed corpus.
+//!
+//! Each target reads its arguments from arbitrary bytes, evaluates them
+//! and panics if the result differs from an exact oracle, or if the library panics.
+//! Only a coefficient overflow with large arguments, a known limit of the library,
+//! ends the run quietly.
+
+use crate::protocol;
+use crate::BigInt;
+use crate::Number;
+use crate::OVERFLOW;
+use std::cmp::Ordering;
+use std::panic;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// Run target over every seed of its corpus, and over every prefix of them.
+    fn replay(target: fn(&[u8]), name: &str) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fuzz")
+            .join("corpus")
+            .join(name);
+        let mut seeds = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let data = fs::read(entry.unwrap().path()).unwrap();
+            for end in 0..=data.len() {
+                target(&data[..end]);
+            }
+            seeds += 1;
+        }
+        assert!(seeds > 0);
+    }
+
+    #[test]
+    fn corpus_homographic() {
+        replay(homographic, "homographic");
+    }
+
+    #[test]
+    fn corpus_combine() {
+        replay(combine, "combine");
+    }
+
+    #[test]
+    fn corpus_symbols() {
+        replay(symbols, "symbols");
+    }
+
+    #[test]
+    fn oracle() {
+        let x = Rational::new(BigInt::from(-6isize), BigInt::from(-4isize)).unwrap();
+        assert_eq!(
+            x.cmp(&Rational::new(BigInt::from(3isize), BigInt::from(2isize)).unwrap()),
+            Ordering::Equal
+        );
+        let big = BigInt::from(isize::MIN);
+        assert_eq!((&big * &big).cmp(&(&big * &-&big)), Ordering::Greater);
+        assert_eq!((&big + &-&big).cmp(&BigInt::from(0isize)), Ordering::Equal);
+        assert_eq!(
+            decode(None, &[])
+                .cmp(&Rational::new(BigInt::from(1isize), BigInt::from(2isize)).unwrap()),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "differs from")]
+    fn detects_disagreement() {
+        let expected = Rational::new(BigInt::from(1isize), BigInt::from(3isize)).unwrap();
+        check(|| Number::ratio(1, 2), Some(expected), false, "test");
+    }
+
+    #[test]
+    #[should_panic(expected = "coefficient overflow")]
+    fn detects_overflow_of_small_arguments() {
+        check(|| panic!("{}", OVERFLOW), None, false, "test");
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn detects_other_panics() {
+        let expected = Rational::new(BigInt::from(1isize), BigInt::from(3isize)).unwrap();
+        check(|| panic!("division by zero"), Some(expected), true, "test");
+    }
+
+    #[test]
+    fn ignores_overflow_of_large_arguments() {
+        check(|| panic!("{}", OVERFLOW), None, true, "test");
+    }
+}
+
+/// Fuzz Number::homographic: an input Number followed by coefficients nx, n, dx, d.
+pub fn homographic(data: &[u8]) {
+    let mut data = Data::new(data);
+    let (x, vx) = data.number();
+    let c: Vec<isize> = (0..4).map(|_| data.coefficient()).collect();
+    let expected = vx.homographic(&c);
+    check(
+        move || Number::homographic(x(), c[0], c[1], c[2], c[3]),
+        expected,
+        data.large,
+        "homographic",
+    );
+}
+
+/// Fuzz Number::combine: two input Numbers followed by coefficients a, b, c, d, e, f, g, h.
+pub fn combine(data: &[u8]) {
+    let mut data = Data::new(data);
+    let (x, vx) = data.number();
+    let (y, vy) = data.number();
+    let c: Vec<isize> = (0..8).map(|_| data.coefficient()).collect();
+    let expected = vx.combine(&vy, &c);
+    check(
+        move || Number::combine(x(), y(), c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]),
+        expected,
+        data.large,
+        "combine",
+    );
+}
+
+/// Fuzz protocol::parse: a word of letters, reprinted and evaluated with Number::from_symbols.
+pub fn symbols(data: &[u8]) {
+    let word = String::from_utf8_lossy(data);
+    let symbols = match protocol::parse(&word) {
+        Ok(symbols) => symbols,
+        Err(_) => return,
+    };
+    let mut printed: String = symbols
+        .iter()
+        .map(|&symbol| protocol::letter(symbol))
+        .collect();
+    // A Special is the whole word; other words end with 'H' when printed.
+    let special = matches!(symbols.first(), Some(protocol::Symbol::Special(_)));
+    if !special {
+        printed.push('H');
+    }
+    assert_eq!(
+        protocol::parse(&printed).as_ref(),
+        Ok(&symbols),
+        "symbols: {}",
+        word
+    );
+    if special {
+        return;
+    }
+    let primer = match symbols.first() {
+        Some(protocol::Symbol::Primer(primer)) => Some(*primer),
+        _ => None,
+    };
+    let reductions: Vec<protocol::Reduction> = symbols
+        .iter()
+        .filter_map(|symbol| match symbol {
+            protocol::Symbol::Reduction(reduction) => Some(*reduction),
+            _ => None,
+        })
+        .collect();
+    let expected = decode(primer, &reductions);
+    let large = reductions.len() > SMALL;
+    check(
+        move || Number::from_symbols(primer, reductions),
+        Some(expected),
+        large,
+        "symbols",
+    );
+}
+
+/// Symbols of a result decoded before taking it for a wrong, irrational one.
+const SYMBOLS: usize = 1 << 16;
+
+/// Largest coefficient, and input length, with which a coefficient overflow is a failure.
+const SMALL: usize = 16;
+
+/**
+ * Evaluate f and check its value against expected.
+ *
+ * Without an expected value the function is undefined at its input,
+ * and f may only panic with a division by zero.
+ * A coefficient overflow is tolerated if the arguments are large.
+ */
+fn check<F: FnOnce() -> Number>(f: F, expected: Option<Rational>, large: bool, target: &str) {
+    let outcome = panic::catch_unwind(panic::AssertUnwindSafe(|| value(f())));
+    let actual = match outcome {
+        Ok(actual) => actual,
+        Err(cause) => {
+            let message = cause
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| cause.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            if (message == OVERFLOW && large)
+                || (expected.is_none() && message == "division by zero")
+            {
+                return;
+            }
+            panic!("{}: {}", target, message);
+        }
+    };
+    if let Some(expected) = expected {
+        match actual {
+            Some(actual) if actual.cmp(&expected) == Ordering::Equal => (),
+            Some(actual) => panic!("{}: result {} differs from {}", target, actual, expected),
+            None => panic!(
+                "{}: result of more than {} symbols differs from {}",
+                target, SYMBOLS, expected
+            ),
+        }
+    }
+}
+
+/// The exact value of x, if it has a limited number of symbols.
+fn value(x: Number) -> Option<Rational> {
+    match x {
+        Number::Special(protocol::Special::NegOne) => {
+            Rational::new(BigInt::from(-1isize), BigInt::from(1isize))
+        }
+        Number::Special(protocol::Special::Zero) => {
+            Rational::new(BigInt::from(0isize), BigInt::from(1isize))
+        }
+        Number::Special(protocol::Special::PosOne) => {
+            Rational::new(BigInt::from(1isize), BigInt::from(1isize))
+        }
+        Number::Other(primer, clog) => {
+            let reductions: Vec<protocol::Reduction> = clog.take(SYMBOLS + 1).collect();
+            if reductions.len() > SYMBOLS {
+                None
+            } else {
+                Some(decode(primer, &reductions))
+            }
+        }
+    }
+}
+
+/// The value of a primer followed by a sequence of reductions.
+fn decode(primer: Option<protocol::Primer>, reductions: &[protocol::Reduction]) -> Rational {
+    // The empty sequence is one half; undo each reduction from the last one.
+    let (mut num, mut den) = (BigInt::from(1isize), BigInt::from(2isize));
+    for reduction in reductions.iter().rev() {
+        match reduction {
+            protocol::Reduction::Amplify => den = &den + &den,
+            protocol::Reduction::Uncover => {
+                let sum = &num + &den;
+                num = den;
+                den = sum;
+            }
+        }
+    }
+    let (num, den) = match primer {
+        None => (num, den),
+        Some(protocol::Primer::Turn) => (den, num),
+        Some(protocol::Primer::Reflect) => (-num, den),
+        Some(protocol::Primer::Ground) => (-den, num),
+    };
+    Rational::new(num, den).unwrap()
+}
+
+/// Arguments read from fuzzer bytes; missing bytes read as zero.
+struct Data<'a> {
+    bytes: &'a [u8],
+    /// Whether any coefficient, or input length, read so far exceeds SMALL.
+    large: bool,
+}
+
+impl<'a> Data<'a> {
+    fn new(bytes: &'a [u8]) -> Data<'a> {
+        Data {
+            bytes,
+            large: false,
+        }
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.bytes.split_first() {
+            Some((byte, rest)) => {
+                self.bytes = rest;
+                *byte
+            }
+            None => 0,
+        }
+    }
+
+    fn bytes(&mut self, n: usize) -> i64 {
+        (0..n).fold(0, |value, _| (value << 8) | i64::from(self.byte()))
+    }
+
+    /// A coefficient that is small, medium, of any size or close to overflow, as told by a tag byte.
+    fn coefficient(&mut self) -> isize {
+        let tag = self.byte();
+        let c = match tag % 4 {
+            0 => self.byte() as i8 as isize,
+            1 => self.bytes(2) as i16 as isize,
+            2 => self.bytes(8) as isize,
+            _ => {
+                let offset = self.byte() as isize;
+                if tag & 4 == 0 {
+                    isize::MAX - offset
+                } else {
+                    isize::MIN + offset
+                }
+            }
+        };
+        self.large |= c.unsigned_abs() > SMALL;
+        c
+    }
+
+    /**
+     * A Number, as a function building it, together with its exact value.
+     *
+     * The Number is either a ratio of coefficients, or read as a primer
+     * followed by up to 63 reductions given by the bits of the following bytes.
+     */
+    fn number(&mut self) -> (Box<dyn FnOnce() -> Number>, Rational) {
+        let tag = self.byte();
+        if tag.is_multiple_of(2) {
+            let num = self.coefficient();
+            let den = match self.coefficient() {
+                0 => 1,
+                den => den,
+            };
+            let value = Rational::new(BigInt::from(num), BigInt::from(den)).unwrap();
+            return (Box::new(move || Number::ratio(num, den)), value);
+        }
+        let primer = match (tag >> 1) % 4 {
+            0 => None,
+            1 => Some(protocol::Primer::Turn),
+            2 => Some(protocol::Primer::Reflect),
+            _ => Some(protocol::Primer::Ground),
+        };
+        let len = usize::from(tag >> 3) % 64;
+        self.large |= len > SMALL;
+        let mut reductions = Vec::with_capacity(len);
+        let mut bits = 0;
+        for i in 0..len {
+            if i % 8 == 0 {
+                bits = self.byte();
+            }
+            reductions.push(if bits & (1 << (i % 8)) == 0 {
+                protocol::Reduction::Amplify
+            } else {
+                protocol::Reduction::Uncover
+            });
+        }
+        let value = decode(primer, &reductions);
+        (
+            Box::new(move || Number::from_symbols(primer, reductions)),
+            value,
+        )
+    }
+}
+
+/// Exact rational number, with positive denominator.
+struct Rational {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl Rational {
+    fn new(num: BigInt, den: BigInt) -> Option<Rational> {
+        match den.signum().cmp(&0) {
+            Ordering::Equal => None,
+            Ordering::Less => Some(Rational {
+                num: -num,
+                den: -den,
+            }),
+            Ordering::Greater => Some(Rational { num, den }),
+        }
+    }
+
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+
+    /// _(nx * self + n) / (dx * self + d)_, if defined.
+    fn homographic(&self, c: &[isize]) -> Option<Rational> {
+        let terms = [self.num.clone(), self.den.clone()];
+        Rational::new(linear(&c[..2], &terms), linear(&c[2..], &terms))
+    }
+
+    /// _(a * self * y + b * self + c * y + d) / (e * self * y + f * self + g * y + h)_, if defined.
+    fn combine(&self, y: &Rational, c: &[isize]) -> Option<Rational> {
+        let terms = [
+            &self.num * &y.num,
+            &self.num * &y.den,
+            &self.den * &y.num,
+            &self.den * &y.den,
+        ];
+        Rational::new(linear(&c[..4], &terms), linear(&c[4..], &terms))
+    }
+}
+
+impl std::fmt::Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+/// _c[0] * terms[0] + c[1] * terms[1] + ..._.
+fn linear(coefficients: &[isize], terms: &[BigInt]) -> BigInt {
+    coefficients
+        .iter()
+        .zip(terms.iter())
+        .fold(BigInt::default(), |sum, (c, term)| {
+            &sum + &(&BigInt::from(*c) * term)
+        })
+}
@@ -26,13 +26,32 @@
 //! Continued logarithm is a way of representing rational numbers with unbounded precision.
 //! Reference: <https://perl.plover.com/classes/cftalk/INFO/gosper.txt>
 
+mod aggregate;
+mod bigint;
 mod compare;
+mod expr;
+mod power;
 mod strategy;
+mod unary;
 
 pub mod protocol;
+pub mod trace;
+
+#[cfg(any(test, feature = "fuzzing"))]
+#[doc(hidden)]
+pub mod fuzz;
+
+pub use crate::bigint::BigInt;
+pub use crate::expr::Expr;
+pub use crate::strategy::consume::Consume;
+pub use crate::strategy::support::OVERFLOW;
 
 use crate::strategy::Strategy;
 use std::cmp::Ordering;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Poll;
 
 /// A number greater than zero and lesser than one with unbounded precision.
 pub struct Clog {
@@ -45,14 +64,55 @@ impl Clog {
      * If self is one half, no symbol is returned.
      */
     pub fn egest(&mut self) -> Option<protocol::Reduction> {
-        match self.strategy.egest() {
-            Ok(reduction) => reduction,
-            Err(new_strategy) => {
-                self.strategy = new_strategy;
-                self.egest()
+        loop {
+            match self.strategy.egest() {
+                Ok(reduction) => return reduction,
+                Err(new_strategy) => self.strategy = new_strategy,
+            }
+        }
+    }
+
+    /**
+     * Same as egest, but return Pending instead of waiting for a symbol
+     * that is not yet available from an input.
+     *
+     * Symbols already determined by the available input are always returned.
+     * Strategies that cannot tell whether a symbol is available may still wait,
+     * as may the construction of a Number, which needs enough input to determine its primer.
+     */
+    pub fn poll_egest(&mut self) -> Poll<Option<protocol::Reduction>> {
+        loop {
+            match self.strategy.poll_egest() {
+                Ok(reduction) => return reduction,
+                Err(new_strategy) => self.strategy = new_strategy,
             }
         }
     }
+
+    /**
+     * Report events of self, including ingestions from the Clogs it depends on, to tracer.
+     *
+     * Tracing is opt-in and adds no cost to Clogs that are not traced.
+     * Several tracers may be set on the same Clog.
+     */
+    pub fn set_tracer<T: trace::Tracer + 'static>(&mut self, tracer: T) {
+        strategy::traced::attach(self, Arc::new(Mutex::new(tracer)));
+    }
+
+    /// Count the work done by self from now on.
+    pub fn profile(&mut self) -> trace::Profile {
+        let (profile, tracer) = trace::Profile::new();
+        self.set_tracer(tracer);
+        profile
+    }
+}
+
+impl Iterator for Clog {
+    type Item = protocol::Reduction;
+
+    fn next(&mut self) -> Option<protocol::Reduction> {
+        self.egest()
+    }
 }
 
 /// A finite unbounded number with unbounded precision.
@@ -61,6 +121,48 @@ pub enum Number {
     Other(Option<protocol::Primer>, Clog),
 }
 
+/// Iterator over the symbols of a Number.
+pub struct Symbols {
+    head: Option<protocol::Symbol>,
+    tail: Option<Clog>,
+}
+
+impl Iterator for Symbols {
+    type Item = protocol::Symbol;
+
+    fn next(&mut self) -> Option<protocol::Symbol> {
+        if let Some(symbol) = self.head.take() {
+            return Some(symbol);
+        }
+        match self.tail.as_mut()?.egest() {
+            Some(reduction) => Some(protocol::Symbol::Reduction(reduction)),
+            None => {
+                self.tail = None;
+                None
+            }
+        }
+    }
+}
+
+impl IntoIterator for Number {
+    type Item = protocol::Symbol;
+    type IntoIter = Symbols;
+
+    /// Destructively iterate over the symbols representing self.
+    fn into_iter(self) -> Symbols {
+        match self {
+            Number::Special(special) => Symbols {
+                head: Some(protocol::Symbol::Special(special)),
+                tail: None,
+            },
+            Number::Other(primer, clog) => Symbols {
+                head: primer.map(protocol::Symbol::Primer),
+                tail: Some(clog),
+            },
+        }
+    }
+}
+
 impl Number {
     /// Unwraps the Special content from self, or panic.
     pub fn unwrap_special(self) -> protocol::Special {
@@ -83,12 +185,33 @@ impl Number {
         compare::compare(n1, n2)
     }
 
+    /// Construct the Number _-x_ in constant time.
+    #[allow(clippy::should_implement_trait)]
+    pub fn neg(x: Number) -> Number {
+        unary::neg(x)
+    }
+
+    /// Construct the Number _|x|_ in constant time.
+    pub fn abs(x: Number) -> Number {
+        unary::abs(x)
+    }
+
+    /// Construct the sign of a Number (minus one, zero or one) in constant time.
+    pub fn signum(x: Number) -> Number {
+        unary::signum(x)
+    }
+
+    /// Construct the Number _1 / x_ in constant time.
+    pub fn recip(x: Number) -> Number {
+        unary::recip(x)
+    }
+
     /// Construct a Number from the ratio of two signed machine integers.
     pub fn ratio(num: isize, den: isize) -> Number {
         Number::ratio_u(
             (num >= 0 && den >= 0) || (num < 0 && den < 0),
-            if num >= 0 { num } else { -num } as usize,
-            if den >= 0 { den } else { -den } as usize,
+            num.unsigned_abs(),
+            den.unsigned_abs(),
         )
     }
 
@@ -130,9 +253,45 @@ impl Number {
         }
     }
 
+    /**
+     * Construct the Number _c[0] + c[1] * x + ... + c[n] * x^n_.
+     */
+    pub fn poly(x: Number, c: &[isize]) -> Number {
+        Number::rational_fn(x, c, &[1])
+    }
+
+    /**
+     * Construct the Number _P(x) / Q(x)_ where _P_ and _Q_ are polynomials
+     * given by their coefficients in ascending order of degree.
+     *
+     * The input is ingested only once, regardless of the degree.
+     * Note that zeros and poles at irrational values of _x_ cannot be decided in finite time.
+     */
+    pub fn rational_fn(x: Number, num: &[isize], den: &[isize]) -> Number {
+        let (special, primer, ratio, polynomial) = strategy::polynomial::new(x, num, den);
+        if let Some(fixed) = special {
+            Number::Special(fixed)
+        } else if let Some(ratio) = ratio {
+            Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(ratio),
+                },
+            )
+        } else {
+            Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(polynomial.unwrap()),
+                },
+            )
+        }
+    }
+
     /**
      * Construct the Number _(nxy * x * y + nx * x + ny * y + n) / (dxy * x * y + dx * x + dy * y + d)_
      */
+    #[allow(clippy::too_many_arguments)]
     pub fn combine(
         x: Number,
         y: Number,
@@ -173,6 +332,160 @@ impl Number {
         }
     }
 
+    /**
+     * Construct the sum of many Numbers.
+     *
+     * Terms are added pairwise in a balanced tree, so the nesting depth of strategies
+     * grows with the logarithm of the number of terms. The sum of no terms is zero.
+     */
+    pub fn sum<I: IntoIterator<Item = Number>>(terms: I) -> Number {
+        aggregate::sum(terms)
+    }
+
+    /**
+     * Construct the product of many Numbers.
+     *
+     * Factors are multiplied pairwise in a balanced tree, so the nesting depth of strategies
+     * grows with the logarithm of the number of factors. The product of no factors is one.
+     */
+    pub fn product<I: IntoIterator<Item = Number>>(factors: I) -> Number {
+        aggregate::product(factors)
+    }
+
+    /**
+     * Split a Number into two Numbers of identical value.
+     *
+     * The input is evaluated only once; its symbols are shared by both outputs.
+     */
+    pub fn fork(x: Number) -> (Number, Number) {
+        strategy::tee::new(x)
+    }
+
+    /**
+     * Evaluate x in a separate thread.
+     *
+     * The thread may produce symbols ahead of the consumer of the result,
+     * and stops when the result is dropped, at its next ingestion or symbol.
+     * Strategies that cannot step by single ingestions, such as those of `from_symbols`
+     * or of third parties, only stop at their next symbol, which may never come.
+     * A panic in the thread is resumed in the consumer.
+     */
+    pub fn spawn(x: Number) -> Number {
+        strategy::spawn::new(x)
+    }
+
+    /// Same as `combine`, but evaluate x and y in parallel threads.
+    #[allow(clippy::too_many_arguments)]
+    pub fn par_combine(
+        x: Number,
+        y: Number,
+        nxy: isize,
+        nx: isize,
+        ny: isize,
+        n: isize,
+        dxy: isize,
+        dx: isize,
+        dy: isize,
+        d: isize,
+    ) -> Number {
+        Number::combine(
+            Number::spawn(x),
+            Number::spawn(y),
+            nxy,
+            nx,
+            ny,
+            n,
+            dxy,
+            dx,
+            dy,
+            d,
+        )
+    }
+
+    /**
+     * Construct a Number from an optional primer followed by a stream of Reduction symbols.
+     *
+     * The stream is read lazily as the result is consumed, so it may be infinite.
+     */
+    pub fn from_symbols<I>(primer: Option<protocol::Primer>, symbols: I) -> Number
+    where
+        I: IntoIterator<Item = protocol::Reduction>,
+        I::IntoIter: Send + 'static,
+    {
+        strategy::stream::new(primer, symbols, None)
+    }
+
+    /**
+     * Same as `from_symbols`, for a stream claimed to represent a rational number.
+     *
+     * Panics if the stream yields more than max_len symbols.
+     */
+    pub fn from_rational_symbols<I>(
+        primer: Option<protocol::Primer>,
+        symbols: I,
+        max_len: usize,
+    ) -> Number
+    where
+        I: IntoIterator<Item = protocol::Reduction>,
+        I::IntoIter: Send + 'static,
+    {
+        strategy::stream::new(primer, symbols, Some(max_len))
+    }
+
+    /**
+     * Construct a Number from an optional primer followed by the Reduction symbols
+     * arriving on receiver, ending when the sender is dropped.
+     *
+     * Symbols may arrive over time; see `Clog::poll_egest` to consume the result
+     * without waiting for them.
+     */
+    pub fn from_receiver(
+        primer: Option<protocol::Primer>,
+        receiver: Receiver<protocol::Reduction>,
+    ) -> Number {
+        strategy::channel::new(primer, receiver)
+    }
+
+    /**
+     * Construct the Number _x^k_.
+     *
+     * The input is shared among the factors by fork instead of being recomputed.
+     */
+    pub fn powi(x: Number, k: isize) -> Number {
+        power::powi(x, k)
+    }
+
+    /**
+     * Construct the Number _x^(p/q)_.
+     *
+     * Panics if _x_ is negative and the reduced _q_ is even.
+     */
+    pub fn powq(x: Number, p: isize, q: isize) -> Number {
+        power::powq(x, p, q)
+    }
+
+    /**
+     * Construct the Number _N(x) / D(x)_, where _N_ and _D_ are multilinear polynomials
+     * of the _k_ inputs _x[0]_, ..., _x[k-1]_.
+     *
+     * Both coefficient slices must have length _2^k_.
+     * The coefficient at index _m_ multiplies the product of every input _x[i]_
+     * for which bit _i_ of _m_ is set; index zero is the constant term.
+     * For example, _(x * y * z + 1) / (x + y + z)_ is given by
+     * `num = [1, 0, 0, 0, 0, 0, 0, 1]` and `den = [0, 1, 1, 0, 1, 0, 0, 0]`.
+     */
+    pub fn multilinear(x: Vec<Number>, num: &[isize], den: &[isize]) -> Number {
+        match strategy::multilinear::new(x, num.to_vec(), den.to_vec()) {
+            (Some(special), _, _) => Number::Special(special),
+            (None, primer, strategy) => Number::Other(
+                primer,
+                Clog {
+                    strategy: strategy.unwrap(),
+                },
+            ),
+        }
+    }
+
     /**
      * Transfer information from a Number to a homograhic transformation.
      *
@@ -190,4 +503,38 @@ impl Number {
         let (co, nx, n, dx, d) = strategy::consume::new(x);
         (co.map(|c| Number::Other(None, c)), nx, n, dx, d)
     }
+
+    /**
+     * Transfer information from a Number to a homograhic transformation with unbounded coefficients.
+     *
+     * Like `consume`, but extraction proceeds until the input reaches one half
+     * or budget symbols are consumed. See `Consume` to resume extraction later.
+     */
+    pub fn consume_unbounded(
+        x: Number,
+        budget: usize,
+    ) -> (Option<Number>, BigInt, BigInt, BigInt, BigInt) {
+        let mut consume = Consume::new(x);
+        consume.consume(budget);
+        consume.into_parts()
+    }
+
+    /**
+     * Construct the Number _(nx * y + n) / (dx * y + d)_, with _y_ taken as one half when None.
+     *
+     * This is the inverse of `consume_unbounded`: the Number continues emitting the symbols
+     * of the consumed input.
+     */
+    pub fn resume(y: Option<Number>, nx: BigInt, n: BigInt, dx: BigInt, d: BigInt) -> Number {
+        Consume::from_parts(y, nx, n, dx, d).into_number()
+    }
+
+    /**
+     * Split x into its integer part and a Number for the remaining fraction in [0, 1).
+     *
+     * Does not return if _x_ is an integer given by an endless stream of symbols.
+     */
+    pub fn split_integer(x: Number) -> (BigInt, Number) {
+        strategy::consume::split_integer(x)
+    }
 }
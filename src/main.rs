@@ -1,93 +1,840 @@
 use clarith::protocol;
-use clarith::Clog;
-use clarith::Number;
+use clarith::trace::{Event, Tracer};
+use clarith::{Expr, Number, OVERFLOW};
+use std::any::Any;
+use std::cmp::Ordering;
+use std::env;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::panic;
+use std::process;
+use std::sync::{Arc, Mutex};
+
+const USAGE: &str = "\
+Usage: clarith [OPTIONS] [EXPRESSION...]
+       clarith [OPTIONS] COMMAND ARGUMENTS...
+
+Evaluate an arithmetic expression exactly, or read one expression per line from stdin.
+
+Expressions are made of decimal numbers, + - * / ^, parentheses,
+sqrt(x) and abs(x). Exponents are integers or parenthesized ratios, as in 2^(1/3).
+
+Commands:
+  encode X                      print the symbols of X
+  decode SYMBOLS                print the value of a symbol string, such as TAAUAH
+  compare X Y                   print <, = or > (never ends if X and Y are equal irrationals)
+  homographic X NX N DX D       evaluate (NX * X + N) / (DX * X + D)
+  combine X Y A B C D E F G H   evaluate (A * X * Y + B * X + C * Y + D) / (E * X * Y + F * X + G * Y + H)
+
+Symbols are Z, P and N for zero, one and minus one; otherwise an optional primer
+T (reciprocal), R (negation) or G (negated reciprocal), followed by reductions
+A (amplify) and U (uncover), and H marking their end.
+
+Options:
+  -p, --precision N  number of decimal places to print (default 20)
+  -s, --symbols      also print the continued logarithm symbols of the result
+  -t, --trace        first print the strategy state at every step of the evaluation
+                     after the primer is chosen; priming steps are not shown
+  -h, --help         print this help";
+
+/// Default number of decimal places.
+const PRECISION: usize = 20;
+
+/// Symbols read from a result before giving up on printing it as a fraction;
+/// longer sequences do not fit the fraction anyway.
+const FRACTION_SYMBOLS: usize = 256;
+
+/// Symbols printed from a result before truncating them.
+const PRINTED_SYMBOLS: usize = 64;
+
+/// Symbols printed by the encode command before truncating them.
+const ENCODED_SYMBOLS: usize = 1024;
+
+/// Panic messages of the library on arguments outside its domain.
+const DOMAIN_ERRORS: [&str; 2] = ["division by zero", "even root of negative number"];
+
+struct Options {
+    precision: usize,
+    symbols: bool,
+    trace: bool,
+}
 
 fn main() {
-    for n in 0..17 {
-        print!("{0: >2}.0: ", n);
-        logn(Number::ratio(n, 1));
-        println!("");
-        print!("{0: >2}.5: ", n);
-        logn(Number::ratio(n * 10 + 5, 10));
-        println!("");
+    let mut options = Options {
+        precision: PRECISION,
+        symbols: false,
+        trace: false,
+    };
+    let mut words = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "-s" | "--symbols" => options.symbols = true,
+            "-t" | "--trace" => options.trace = true,
+            "-p" | "--precision" => match args.next().map(|p| p.parse()) {
+                Some(Ok(precision)) => options.precision = precision,
+                _ => fail("expected a number of decimal places after --precision"),
+            },
+            _ => words.push(arg),
+        }
+    }
+
+    // Library panics on arguments outside its domain, such as a division by zero,
+    // and coefficient overflows are reported by the commands; any other panic is a bug
+    // and is also shown where it happened.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let message = panic_message(info.payload());
+        if !is_overflow(info.payload()) && !DOMAIN_ERRORS.contains(&message.as_str()) {
+            default_hook(info);
+        }
+    }));
+
+    if let Some(command) = words.first() {
+        let arguments = &words[1..];
+        let result = match command.as_str() {
+            "encode" => encode(arguments),
+            "decode" => decode_symbols(arguments),
+            "compare" => compare(arguments),
+            "homographic" => homographic(arguments, &options),
+            "combine" => combine(arguments, &options),
+            _ => calculate(&words.join(" "), &options),
+        };
+        if let Err(message) = result {
+            fail(&message);
+        }
+        return;
+    }
+    let stdin = io::stdin();
+    let interactive = stdin.is_terminal();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().unwrap();
+        }
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Err(message) = calculate(line, &options) {
+            println!("error: {}", message);
+        }
     }
+}
 
-    print!("{}: ", isize::max_value());
-    logn(Number::ratio(isize::max_value(), 1));
-    println!("");
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1);
+}
 
-    print!("1/{}: ", isize::max_value());
-    logn(Number::ratio(1, isize::max_value()));
-    println!("");
+/// Evaluate an expression and print its value.
+fn calculate(line: &str, options: &Options) -> Result<(), String> {
+    report(|| evaluate(line), options)
+}
 
-    print!("-2: ");
-    logn(Number::ratio(-2, 1));
-    println!("");
+/// Parse and evaluate an expression.
+fn evaluate(line: &str) -> Result<Number, String> {
+    Parser::new(line).parse().map(Expr::eval)
 }
 
-fn logn(x: Number) {
-    match x {
-        Number::Special(s) => {
-            logs(s);
+/**
+ * Print the value given by evaluate.
+ *
+ * The value is evaluated anew for each form of output,
+ * so that an overflow while producing one of them does not spoil the others.
+ */
+fn report<F: Fn() -> Result<Number, String>>(evaluate: F, options: &Options) -> Result<(), String> {
+    if options.trace {
+        guard(|| evaluate().and_then(trace))?;
+    }
+    let mut output = vec![guard(|| {
+        evaluate().and_then(|x| decimal(x, options.precision))
+    })?];
+    if options.symbols {
+        output.push(guard(|| {
+            evaluate().and_then(|x| symbols(x, PRINTED_SYMBOLS))
+        })?);
+    }
+    // Reading far enough may exceed what the strategies can represent,
+    // which only means that no fraction is printed.
+    if let Some(Some((num, den))) = limited(|| evaluate().map(fraction))?.transpose()? {
+        if den != 1 {
+            output.push(format!("= {}/{}", num, den));
         }
-        Number::Other(op, c) => {
-            if let Some(p) = op {
-                logp(p);
-            }
-            logc(c);
+    }
+    for line in output {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// The arguments of a command, if there are as many as names.
+fn expect_arguments<'a>(
+    command: &str,
+    arguments: &'a [String],
+    names: &[&str],
+) -> Result<&'a [String], String> {
+    if arguments.len() == names.len() {
+        Ok(arguments)
+    } else {
+        Err(format!("usage: clarith {} {}", command, names.join(" ")))
+    }
+}
+
+fn coefficients(arguments: &[String]) -> Result<Vec<isize>, String> {
+    arguments
+        .iter()
+        .map(|c| {
+            c.parse()
+                .map_err(|_| format!("invalid coefficient '{}'", c))
+        })
+        .collect()
+}
+
+fn encode(arguments: &[String]) -> Result<(), String> {
+    let x = &expect_arguments("encode", arguments, &["X"])?[0];
+    println!(
+        "{}",
+        guard(|| evaluate(x).and_then(|x| symbols(x, ENCODED_SYMBOLS)))?
+    );
+    Ok(())
+}
+
+fn decode_symbols(arguments: &[String]) -> Result<(), String> {
+    let word = &expect_arguments("decode", arguments, &["SYMBOLS"])?[0];
+    let (num, den) = parse_symbols(word)?;
+    if den == 1 {
+        println!("{}", num);
+    } else {
+        println!("{}/{}", num, den);
+    }
+    Ok(())
+}
+
+fn compare(arguments: &[String]) -> Result<(), String> {
+    let xy = expect_arguments("compare", arguments, &["X", "Y"])?;
+    let ordering = guard(|| Ok(Number::compare(evaluate(&xy[0])?, evaluate(&xy[1])?)))?;
+    println!(
+        "{}",
+        match ordering {
+            Ordering::Less => "<",
+            Ordering::Equal => "=",
+            Ordering::Greater => ">",
         }
+    );
+    Ok(())
+}
+
+fn homographic(arguments: &[String], options: &Options) -> Result<(), String> {
+    let arguments = expect_arguments("homographic", arguments, &["X", "NX", "N", "DX", "D"])?;
+    let c = coefficients(&arguments[1..])?;
+    report(
+        || {
+            Ok(Number::homographic(
+                evaluate(&arguments[0])?,
+                c[0],
+                c[1],
+                c[2],
+                c[3],
+            ))
+        },
+        options,
+    )
+}
+
+fn combine(arguments: &[String], options: &Options) -> Result<(), String> {
+    let arguments = expect_arguments(
+        "combine",
+        arguments,
+        &["X", "Y", "A", "B", "C", "D", "E", "F", "G", "H"],
+    )?;
+    let c = coefficients(&arguments[2..])?;
+    report(
+        || {
+            Ok(Number::combine(
+                evaluate(&arguments[0])?,
+                evaluate(&arguments[1])?,
+                c[0],
+                c[1],
+                c[2],
+                c[3],
+                c[4],
+                c[5],
+                c[6],
+                c[7],
+            ))
+        },
+        options,
+    )
+}
+
+/// Run f, reporting a panic, including a coefficient overflow, as an error.
+fn guard<T, F: FnOnce() -> Result<T, String>>(f: F) -> Result<T, String> {
+    panic::catch_unwind(panic::AssertUnwindSafe(f))
+        .unwrap_or_else(|cause| Err(panic_message(cause.as_ref())))
+}
+
+/// Run f, giving None if the coefficients of a strategy overflow and reporting any other panic as an error.
+fn limited<T, F: FnOnce() -> T>(f: F) -> Result<Option<T>, String> {
+    match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(value) => Ok(Some(value)),
+        Err(cause) if is_overflow(cause.as_ref()) => Ok(None),
+        Err(cause) => Err(panic_message(cause.as_ref())),
+    }
+}
+
+fn panic_message(cause: &(dyn Any + Send)) -> String {
+    match cause.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match cause.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "evaluation failed".to_string(),
+        },
     }
 }
 
-fn logs(s: protocol::Special) {
-    match s {
-        protocol::Special::Zero => {
-            print!("Z");
+/**
+ * Whether a panic comes from coefficients exceeding isize, as raised by the library.
+ *
+ * Anything else, such as a logic error, is a bug rather than a limit of the strategies.
+ */
+fn is_overflow(cause: &(dyn Any + Send)) -> bool {
+    panic_message(cause) == OVERFLOW
+}
+
+/**
+ * Print a table of the strategy state at every step of egesting x,
+ * up to the egestion of a limited number of symbols.
+ *
+ * The tracer is set on the Clog of x, which its constructor has already primed:
+ * the ingestions and strategy switches made while choosing the primer are not shown.
+ * A coefficient overflow ends the table early.
+ */
+fn trace(x: Number) -> Result<(), String> {
+    let (primer, mut clog) = match x {
+        Number::Special(special) => {
+            println!(
+                "{}: no strategy to trace",
+                protocol::letter(protocol::Symbol::Special(special))
+            );
+            return Ok(());
         }
-        protocol::Special::PosOne => {
-            print!("P");
+        Number::Other(primer, clog) => (primer, clog),
+    };
+    if let Some(primer) = primer {
+        println!(
+            "primer {}",
+            protocol::letter(protocol::Symbol::Primer(primer))
+        );
+    }
+    let table = Arc::new(Mutex::new(Table::default()));
+    table.lock().unwrap().header();
+    let tracer = Arc::clone(&table);
+    clog.set_tracer(move |event: &Event| tracer.lock().unwrap().trace(event));
+    let mut egested = 0;
+    let ended = limited(|| {
+        while egested < PRINTED_SYMBOLS && clog.egest().is_some() {
+            egested += 1;
         }
-        protocol::Special::NegOne => {
-            print!("N");
+    });
+    // The tracer may have been poisoned by a panic within it.
+    let mut table = table
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    table.flush();
+    match ended? {
+        None => println!("(stopped by coefficient overflow)"),
+        Some(()) if egested == PRINTED_SYMBOLS => println!("..."),
+        Some(()) => (),
+    }
+    Ok(())
+}
+
+/// Tracer printing one row per step: the decision taken and the state it left.
+#[derive(Default)]
+struct Table {
+    step: usize,
+    decision: Vec<String>,
+    strategy: Option<&'static str>,
+    coefficients: Vec<isize>,
+    image: Vec<isize>,
+    /// Inputs already reported as ended; some strategies keep asking them.
+    ended: Vec<usize>,
+}
+
+impl Table {
+    fn header(&self) {
+        println!("(steps after priming; priming is not traced)");
+        println!(
+            "{:>5}  {:<12} {:<24} {:<40} image",
+            "step", "strategy", "decision", "coefficients"
+        );
+    }
+
+    /// Print the row of the current step, if any.
+    fn flush(&mut self) {
+        if self.decision.is_empty() && self.strategy.is_none() {
+            return;
         }
+        self.step += 1;
+        let image = match self.image.as_slice() {
+            [nmin, dmin, nmax, dmax] => format!("{}/{} .. {}/{}", nmin, dmin, nmax, dmax),
+            _ => String::new(),
+        };
+        let row = format!(
+            "{:>5}  {:<12} {:<24} {:<40} {}",
+            self.step,
+            self.strategy.unwrap_or(""),
+            self.decision.join(", "),
+            format!("{:?}", self.coefficients),
+            image
+        );
+        println!("{}", row.trim_end());
+        self.decision.clear();
+        self.strategy = None;
+        self.coefficients.clear();
+        self.image.clear();
     }
 }
 
-fn logp(p: protocol::Primer) {
-    match p {
-        protocol::Primer::Turn => {
-            print!("T");
+impl Tracer for Table {
+    fn trace(&mut self, event: &Event) {
+        match event {
+            Event::Ingest(input, None) if self.ended.contains(input) => (),
+            Event::Ingest(input, reduction) => {
+                // An ingestion starts a new step once the previous one reported its state.
+                if self.strategy.is_some() {
+                    self.flush();
+                }
+                let name = match input {
+                    0 => "x".to_string(),
+                    1 => "y".to_string(),
+                    i => format!("input {}", i),
+                };
+                self.decision.push(match reduction {
+                    Some(reduction) => format!(
+                        "ingest {} {}",
+                        name,
+                        protocol::letter(protocol::Symbol::Reduction(*reduction))
+                    ),
+                    None => {
+                        self.ended.push(*input);
+                        format!("{} ended", name)
+                    }
+                });
+            }
+            Event::Coefficients(name, coefficients) => {
+                if self.strategy.is_some() {
+                    self.flush();
+                }
+                self.strategy = Some(name);
+                self.coefficients = coefficients.to_vec();
+            }
+            Event::Image(image) => self.image = image.to_vec(),
+            Event::Reduced(_) => self.decision.push("reduce".to_string()),
+            Event::Egest(reduction) => {
+                self.decision.push(match reduction {
+                    Some(reduction) => {
+                        format!(
+                            "egest {}",
+                            protocol::letter(protocol::Symbol::Reduction(*reduction))
+                        )
+                    }
+                    None => "end".to_string(),
+                });
+                self.flush();
+            }
+            Event::Switch(name) => {
+                self.decision.push(format!("switch to {}", name));
+                self.flush();
+            }
+        }
+    }
+}
+
+/// Recursive descent parser of expressions.
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn new(line: &str) -> Parser {
+        Parser {
+            chars: line.chars().collect(),
+            position: 0,
+        }
+    }
+
+    fn parse(mut self) -> Result<Expr, String> {
+        let expr = self.sum()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("{} at column {}", message, self.position + 1)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+        self.chars.get(self.position).cloned()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut x = self.product()?;
+        loop {
+            if self.eat('+') {
+                x = Expr::add(&x, &self.product()?);
+            } else if self.eat('-') {
+                x = Expr::sub(&x, &self.product()?);
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut x = self.unary()?;
+        loop {
+            if self.eat('*') {
+                x = Expr::mul(&x, &self.unary()?);
+            } else if self.eat('/') {
+                x = Expr::div(&x, &self.unary()?);
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat('-') {
+            Ok(Expr::neg(&self.unary()?))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expr, String> {
+        let x = self.atom()?;
+        if !self.eat('^') {
+            return Ok(x);
+        }
+        let (p, q) = if self.eat('(') {
+            let p = self.integer()?;
+            let q = if self.eat('/') { self.integer()? } else { 1 };
+            self.expect(')')?;
+            (p, q)
+        } else {
+            (self.integer()?, 1)
+        };
+        if q == 0 {
+            return Err(self.error("zero exponent denominator"));
+        }
+        Ok(Expr::var(if q == 1 {
+            Number::powi(x.eval(), p)
+        } else {
+            Number::powq(x.eval(), p, q)
+        }))
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let x = self.sum()?;
+                self.expect(')')?;
+                Ok(x)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.literal(),
+            Some(c) if c.is_alphabetic() => {
+                let start = self.position;
+                while self.position < self.chars.len() && self.chars[self.position].is_alphabetic()
+                {
+                    self.position += 1;
+                }
+                let name: String = self.chars[start..self.position].iter().collect();
+                self.expect('(')?;
+                let x = self.sum()?;
+                self.expect(')')?;
+                match name.as_str() {
+                    "sqrt" => Ok(Expr::var(Number::powq(x.eval(), 1, 2))),
+                    "abs" => Ok(Expr::var(Number::abs(x.eval()))),
+                    _ => {
+                        self.position = start;
+                        Err(self.error(&format!("unknown function '{}'", name)))
+                    }
+                }
+            }
+            Some(c) => Err(self.error(&format!("unexpected '{}'", c))),
+            None => Err(self.error("unexpected end of expression")),
+        }
+    }
+
+    fn digits(&mut self) -> String {
+        let start = self.position;
+        while self.position < self.chars.len() && self.chars[self.position].is_ascii_digit() {
+            self.position += 1;
         }
-        protocol::Primer::Reflect => {
-            print!("R");
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn integer(&mut self) -> Result<isize, String> {
+        let negative = self.eat('-');
+        self.peek();
+        let digits = self.digits();
+        if digits.is_empty() {
+            return Err(self.error("expected an integer"));
+        }
+        match digits.parse::<isize>() {
+            Ok(n) if negative => Ok(-n),
+            Ok(n) => Ok(n),
+            Err(_) => Err(self.error("integer too large")),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Expr, String> {
+        let mut digits = self.digits();
+        let mut den: isize = 1;
+        if self.position < self.chars.len() && self.chars[self.position] == '.' {
+            self.position += 1;
+            let fraction = self.digits();
+            for _ in 0..fraction.len() {
+                den = den
+                    .checked_mul(10)
+                    .ok_or_else(|| self.error("literal too long"))?;
+            }
+            digits.push_str(&fraction);
+        }
+        if digits.is_empty() {
+            return Err(self.error("expected a number"));
         }
-        protocol::Primer::Ground => {
-            print!("G");
+        match digits.parse::<isize>() {
+            Ok(num) => Ok(Expr::ratio(num, den)),
+            Err(_) => Err(self.error("literal too large")),
         }
     }
 }
 
-fn logc(mut c: Clog) {
+/// Whether x is less than n, and x itself.
+fn is_less(x: Number, n: isize) -> (bool, Number) {
+    let (x, probe) = Number::fork(x);
+    (
+        Number::compare(probe, Number::ratio(n, 1)) == Ordering::Less,
+        x,
+    )
+}
+
+/// The largest integer not greater than x, and x minus that integer.
+fn floor(x: Number) -> (isize, Number) {
+    let (negative, mut x) = is_less(x, 0);
+    let (mut lo, mut hi) = if negative { (-1, 0) } else { (0, 1) };
     loop {
-        match c.egest() {
-            None => {
-                print!("H");
-                break;
+        let bound = if negative { lo } else { hi };
+        let (less, y) = is_less(x, bound);
+        x = y;
+        if less != negative {
+            break;
+        }
+        if negative {
+            hi = lo;
+            lo = lo.checked_mul(2).expect(OVERFLOW);
+        } else {
+            lo = hi;
+            hi = hi.checked_mul(2).expect(OVERFLOW);
+        }
+    }
+    while hi - lo > 1 {
+        let middle = lo + (hi - lo) / 2;
+        let (less, y) = is_less(x, middle);
+        x = y;
+        if less {
+            hi = middle;
+        } else {
+            lo = middle;
+        }
+    }
+    (lo, Number::homographic(x, 1, -lo, 0, 1))
+}
+
+/// Whether x is zero, and x itself.
+fn is_zero(x: Number) -> (bool, Number) {
+    let (x, probe) = Number::fork(x);
+    (
+        Number::compare(probe, Number::ratio(0, 1)) == Ordering::Equal,
+        x,
+    )
+}
+
+/**
+ * The decimal expansion of x, truncated to a number of places and marked by an ellipsis if inexact.
+ *
+ * Fewer places are given, with a note, if the strategies overflow before reaching the requested precision.
+ */
+fn decimal(x: Number, places: usize) -> Result<String, String> {
+    let (negative, x) = is_less(x, 0);
+    let x = if negative { Number::neg(x) } else { x };
+    let (integer, fraction) = floor(x);
+    let mut output = format!("{}{}", if negative { "-" } else { "" }, integer);
+    let mut digits = String::new();
+    let mut fraction = Some(fraction);
+    let mut exact = false;
+    let mut overflow = false;
+    while let Some(x) = fraction.take() {
+        // Each step is isolated, so that an overflow keeps the digits found so far.
+        let step = limited(|| {
+            let (zero, x) = is_zero(x);
+            if zero || digits.len() == places {
+                (zero, None)
+            } else {
+                (false, Some(floor(Number::homographic(x, 10, 0, 0, 1))))
             }
-            Some(r) => {
-                logr(r);
+        })?;
+        match step {
+            Some((zero, next)) => {
+                exact = zero;
+                if let Some((digit, x)) = next {
+                    digits.push_str(&digit.to_string());
+                    fraction = Some(x);
+                }
             }
+            None => overflow = true,
         }
     }
+    if !digits.is_empty() {
+        output.push('.');
+        output.push_str(&digits);
+    }
+    if !exact {
+        output.push_str("...");
+    }
+    if overflow {
+        output.push_str(" (precision limited by coefficient overflow)");
+    }
+    Ok(output)
 }
 
-fn logr(r: protocol::Reduction) {
-    match r {
-        protocol::Reduction::Amplify => {
-            print!("A");
+/// The value of x as a reduced fraction, if its symbols end soon enough and it fits.
+fn fraction(x: Number) -> Option<(i128, i128)> {
+    let mut reductions = Vec::new();
+    let primer = match x {
+        Number::Special(protocol::Special::NegOne) => return Some((-1, 1)),
+        Number::Special(protocol::Special::Zero) => return Some((0, 1)),
+        Number::Special(protocol::Special::PosOne) => return Some((1, 1)),
+        Number::Other(primer, mut clog) => {
+            while let Some(reduction) = clog.egest() {
+                if reductions.len() == FRACTION_SYMBOLS {
+                    return None;
+                }
+                reductions.push(reduction);
+            }
+            primer
         }
-        protocol::Reduction::Uncover => {
-            print!("U");
+    };
+    decode(primer, &reductions)
+}
+
+/// The value of a symbol string, as printed by symbols.
+fn parse_symbols(word: &str) -> Result<(i128, i128), String> {
+    let symbols = protocol::parse(word)?;
+    let (primer, reductions) = match symbols.split_first() {
+        Some((protocol::Symbol::Special(protocol::Special::Zero), _)) => return Ok((0, 1)),
+        Some((protocol::Symbol::Special(protocol::Special::PosOne), _)) => return Ok((1, 1)),
+        Some((protocol::Symbol::Special(protocol::Special::NegOne), _)) => return Ok((-1, 1)),
+        Some((protocol::Symbol::Primer(primer), rest)) => (Some(*primer), rest),
+        _ => (None, &symbols[..]),
+    };
+    let reductions: Vec<protocol::Reduction> = reductions
+        .iter()
+        .filter_map(|symbol| match symbol {
+            protocol::Symbol::Reduction(reduction) => Some(*reduction),
+            _ => None,
+        })
+        .collect();
+    decode(primer, &reductions).ok_or_else(|| "value too large".to_string())
+}
+
+/// The value represented by a primer and a finite sequence of Reduction symbols.
+fn decode(
+    primer: Option<protocol::Primer>,
+    reductions: &[protocol::Reduction],
+) -> Option<(i128, i128)> {
+    // The empty sequence is one half; undo each reduction from the last one.
+    let (mut num, mut den): (i128, i128) = (1, 2);
+    for reduction in reductions.iter().rev() {
+        match reduction {
+            protocol::Reduction::Amplify => {
+                if num % 2 == 0 {
+                    num /= 2;
+                } else {
+                    den = den.checked_mul(2)?;
+                }
+            }
+            protocol::Reduction::Uncover => {
+                let sum = num.checked_add(den)?;
+                num = den;
+                den = sum;
+            }
+        }
+    }
+    Some(match primer {
+        None => (num, den),
+        Some(protocol::Primer::Turn) => (den, num),
+        Some(protocol::Primer::Reflect) => (-num, den),
+        Some(protocol::Primer::Ground) => (-den, num),
+    })
+}
+
+/// The symbols of x as letters, up to a limit; 'H' marks the end of a Reduction sequence.
+fn symbols(x: Number, limit: usize) -> Result<String, String> {
+    let end = match x {
+        Number::Special(_) => "",
+        Number::Other(_, _) => "H",
+    };
+    let mut output = String::new();
+    let mut symbols = x.into_iter();
+    loop {
+        if output.len() == limit {
+            output.push_str("...");
+            return Ok(output);
+        }
+        match limited(|| symbols.next())? {
+            Some(Some(symbol)) => output.push(protocol::letter(symbol)),
+            Some(None) => break,
+            None => {
+                output.push_str("... (limited by coefficient overflow)");
+                return Ok(output);
+            }
         }
     }
+    output.push_str(end);
+    Ok(output)
 }
@@ -0,0 +1,167 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::strategy::root;
+use crate::strategy::support::gcd;
+use crate::Clog;
+use crate::Number;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    #[test]
+    fn powi_zero_exponent() {
+        assert_eq(powi(Number::ratio(-2, 3), 0), Number::ratio(1, 1));
+        assert_eq(powi(Number::ratio(0, 1), 0), Number::ratio(1, 1));
+    }
+
+    #[test]
+    fn powi_positive_exponent() {
+        assert_eq(powi(Number::ratio(2, 3), 1), Number::ratio(2, 3));
+        assert_eq(powi(Number::ratio(2, 3), 2), Number::ratio(4, 9));
+        assert_eq(powi(Number::ratio(-2, 3), 3), Number::ratio(-8, 27));
+        assert_eq(powi(Number::ratio(3, 2), 5), Number::ratio(243, 32));
+        assert_eq(powi(Number::ratio(-1, 2), 10), Number::ratio(1, 1024));
+        assert_eq(powi(Number::ratio(-1, 1), 7), Number::ratio(-1, 1));
+        assert_eq(powi(Number::ratio(0, 1), 3), Number::ratio(0, 1));
+    }
+
+    #[test]
+    fn powi_negative_exponent() {
+        assert_eq(powi(Number::ratio(2, 3), -1), Number::ratio(3, 2));
+        assert_eq(powi(Number::ratio(-2, 3), -3), Number::ratio(-27, 8));
+        assert_eq(powi(Number::ratio(5, 1), -2), Number::ratio(1, 25));
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn powi_forbids_reciprocal_of_zero() {
+        powi(Number::ratio(0, 1), -1);
+    }
+
+    #[test]
+    fn powq_integer_exponent() {
+        assert_eq(powq(Number::ratio(2, 3), 4, 2), Number::ratio(4, 9));
+        assert_eq(powq(Number::ratio(2, 3), -3, -1), Number::ratio(8, 27));
+    }
+
+    #[test]
+    fn powq_perfect_roots() {
+        assert_eq(powq(Number::ratio(4, 1), 1, 2), Number::ratio(2, 1));
+        assert_eq(powq(Number::ratio(27, 8), 2, 3), Number::ratio(9, 4));
+        assert_eq(powq(Number::ratio(-8, 1), 1, 3), Number::ratio(-2, 1));
+        assert_eq(powq(Number::ratio(4, 9), -1, 2), Number::ratio(3, 2));
+        assert_eq(powq(Number::ratio(-8, 27), 4, 6), Number::ratio(4, 9));
+    }
+
+    #[test]
+    fn powq_irrational() {
+        // 2^(-1/2) = 0.70710...
+        assert_eq!(
+            Number::compare(powq(Number::ratio(2, 1), -1, 2), Number::ratio(7071, 10000)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Number::compare(powq(Number::ratio(2, 1), -1, 2), Number::ratio(7072, 10000)),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn powq_forbids_zero_denominator() {
+        powq(Number::ratio(2, 1), 1, 0);
+    }
+}
+
+pub fn powi(x: Number, k: isize) -> Number {
+    if k == 0 {
+        return Number::ratio(1, 1);
+    }
+    let power = powu(x, k.unsigned_abs());
+    if k > 0 {
+        power
+    } else {
+        Number::recip(power)
+    }
+}
+
+pub fn powq(x: Number, mut p: isize, mut q: isize) -> Number {
+    if q == 0 {
+        panic!("division by zero");
+    }
+    let g = gcd(p, q);
+    p /= g;
+    q /= g;
+    if q < 0 {
+        p = -p;
+        q = -q;
+    }
+    if q == 1 {
+        return powi(x, p);
+    }
+    let root = match root::new(x, q as usize) {
+        (Some(special), _, _) => Number::Special(special),
+        (None, primer, root) => Number::Other(
+            primer,
+            Clog {
+                strategy: Box::new(root.unwrap()),
+            },
+        ),
+    };
+    powi(root, p)
+}
+
+/// Exponentiation by squaring, for positive exponents.
+fn powu(x: Number, mut k: usize) -> Number {
+    let mut power: Option<Number> = None;
+    let mut base = x;
+    loop {
+        if !k.is_multiple_of(2) {
+            if k == 1 {
+                return match power {
+                    None => base,
+                    Some(power) => mul(power, base),
+                };
+            }
+            let (b1, b2) = Number::fork(base);
+            base = b1;
+            power = Some(match power {
+                None => b2,
+                Some(power) => mul(power, b2),
+            });
+        }
+        k /= 2;
+        let (b1, b2) = Number::fork(base);
+        base = mul(b1, b2);
+    }
+}
+
+fn mul(x: Number, y: Number) -> Number {
+    Number::combine(x, y, 1, 0, 0, 0, 0, 0, 0, 1)
+}
@@ -26,6 +26,44 @@
  * Finally, the Special enum covers the holes of the representation scheme.
  */
 
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_letters() {
+        assert_eq!(parse("N"), Ok(vec![Symbol::Special(Special::NegOne)]));
+        assert_eq!(parse(""), Ok(vec![]));
+        assert_eq!(
+            parse("TAAUAH"),
+            Ok(vec![
+                Symbol::Primer(Primer::Turn),
+                Symbol::Reduction(Reduction::Amplify),
+                Symbol::Reduction(Reduction::Amplify),
+                Symbol::Reduction(Reduction::Uncover),
+                Symbol::Reduction(Reduction::Amplify),
+            ])
+        );
+        let word = "GAUUUAUUUAAUUUU";
+        let letters: String = parse(word).unwrap().into_iter().map(letter).collect();
+        assert_eq!(letters, word);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("ZA"),
+            Err("unexpected 'A' after special symbol".to_string())
+        );
+        assert_eq!(parse("TAX"), Err("unexpected 'X' in symbols".to_string()));
+        assert_eq!(
+            parse("UAAHA"),
+            Err("unexpected 'A' after end of symbols".to_string())
+        );
+    }
+}
+
 /**
  * The Reduction enum defines the symbols that allow any number greater than zero
  * and lesser than one to be represented in a continued logarithm format.
@@ -41,7 +79,7 @@
  *  - 0.567 = 'UUAUUUUUUUAAAAUA'
  *  - 0.888888... = 'UAA'
  */
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Reduction {
     /// The value was greater than zero and lesser than one half, and was doubled.
     Amplify,
@@ -60,7 +98,7 @@ pub enum Reduction {
  *  - 5 = 'TAAUA'
  *  - -3.14 = 'GAUUUAUUUAAUUUU'
  */
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Primer {
     /// The value was reciprocated.
     Turn,
@@ -73,7 +111,7 @@ pub enum Primer {
 /**
  * Values that cannot be represented by the combination of an optional Primer and a Reduction sequence.
  */
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Special {
     /// The value is minus one.
     NegOne,
@@ -82,3 +120,83 @@ pub enum Special {
     /// The value is one.
     PosOne,
 }
+
+/**
+ * Any symbol in the representation of a Number:
+ * either a single Special, or an optional Primer followed by a sequence of Reductions.
+ *
+ * Examples:
+ *  - 0 = [Special(Zero)]
+ *  - 5 = [Primer(Turn), Reduction(Amplify), Reduction(Amplify), Reduction(Uncover), Reduction(Amplify)]
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symbol {
+    Special(Special),
+    Primer(Primer),
+    Reduction(Reduction),
+}
+
+/**
+ * The letter of a symbol, as in the examples above:
+ * 'Z', 'P' and 'N' for the Specials, 'T', 'R' and 'G' for the Primers, 'A' and 'U' for the Reductions.
+ */
+pub fn letter(symbol: Symbol) -> char {
+    match symbol {
+        Symbol::Special(Special::Zero) => 'Z',
+        Symbol::Special(Special::PosOne) => 'P',
+        Symbol::Special(Special::NegOne) => 'N',
+        Symbol::Primer(Primer::Turn) => 'T',
+        Symbol::Primer(Primer::Reflect) => 'R',
+        Symbol::Primer(Primer::Ground) => 'G',
+        Symbol::Reduction(Reduction::Amplify) => 'A',
+        Symbol::Reduction(Reduction::Uncover) => 'U',
+    }
+}
+
+/**
+ * The symbols of a word of letters: either a single Special,
+ * or an optional Primer followed by Reductions and an optional 'H' marking their end.
+ *
+ * Examples:
+ *  - 'Z' = [Special(Zero)]
+ *  - 'RAH' = [Primer(Reflect), Reduction(Amplify)]
+ */
+pub fn parse(word: &str) -> Result<Vec<Symbol>, String> {
+    let mut letters = word.chars().peekable();
+    let special = match letters.peek() {
+        Some('Z') => Some(Special::Zero),
+        Some('P') => Some(Special::PosOne),
+        Some('N') => Some(Special::NegOne),
+        _ => None,
+    };
+    if let Some(special) = special {
+        letters.next();
+        return match letters.next() {
+            None => Ok(vec![Symbol::Special(special)]),
+            Some(c) => Err(format!("unexpected '{}' after special symbol", c)),
+        };
+    }
+    let mut symbols = Vec::new();
+    let primer = match letters.peek() {
+        Some('T') => Some(Primer::Turn),
+        Some('R') => Some(Primer::Reflect),
+        Some('G') => Some(Primer::Ground),
+        _ => None,
+    };
+    if let Some(primer) = primer {
+        letters.next();
+        symbols.push(Symbol::Primer(primer));
+    }
+    loop {
+        match letters.next() {
+            Some('A') => symbols.push(Symbol::Reduction(Reduction::Amplify)),
+            Some('U') => symbols.push(Symbol::Reduction(Reduction::Uncover)),
+            Some('H') | None => break,
+            Some(c) => return Err(format!("unexpected '{}' in symbols", c)),
+        }
+    }
+    match letters.next() {
+        None => Ok(symbols),
+        Some(c) => Err(format!("unexpected '{}' after end of symbols", c)),
+    }
+}
@@ -18,14 +18,127 @@
  * along with clarith.  If not, see <http://www.gnu.org/licenses/>
  */
 
+pub mod channel;
 pub mod combine;
 pub mod consume;
 pub mod homographic;
+pub mod multilinear;
+pub mod polynomial;
 pub mod ratio;
-mod support;
+pub mod root;
+pub mod spawn;
+pub mod stream;
+pub(crate) mod support;
+pub mod tee;
+pub mod traced;
 
 use crate::protocol;
+use crate::Clog;
+use std::any::{type_name, Any};
+use std::task::Poll;
 
-pub trait Strategy {
+#[cfg(test)]
+pub mod tests {
+
+    use super::*;
+    use crate::Number;
+
+    /// Egest a number of Amplify symbols, then end.
+    struct Amplifier(usize);
+
+    impl Strategy for Amplifier {
+        fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+            if self.0 == 0 {
+                return Ok(None);
+            }
+            self.0 -= 1;
+            Ok(Some(protocol::Reduction::Amplify))
+        }
+    }
+
+    /// Switch to another strategy a number of times, then end.
+    struct Relay(usize);
+
+    impl Strategy for Relay {
+        fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+            if self.0 == 0 {
+                return Ok(None);
+            }
+            Err(Box::new(Relay(self.0 - 1)))
+        }
+    }
+
+    /// A Number whose Clog is a long stream of Amplify symbols.
+    pub fn amplified(n: usize) -> Number {
+        Number::Other(
+            None,
+            Clog {
+                strategy: Box::new(Amplifier(n)),
+            },
+        )
+    }
+
+    /// A Number whose Clog switches strategies a number of times before it ends.
+    pub fn relayed(n: usize) -> Number {
+        Number::Other(
+            None,
+            Clog {
+                strategy: Box::new(Relay(n)),
+            },
+        )
+    }
+
+    #[test]
+    fn long_relay() {
+        let (_, mut clog) = relayed(300_000).unwrap_other();
+        assert_eq!(clog.egest(), None);
+    }
+}
+
+/// What a strategy did in a single step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    /// Symbols were taken from inputs.
+    Ingest,
+    /// A symbol was produced; None means the end.
+    Egest(Option<protocol::Reduction>),
+}
+
+pub trait Strategy: Any + Send + Sync {
     fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>>;
+
+    /// Same as egest, but return Pending instead of waiting for an input symbol.
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        self.egest().map(Poll::Ready)
+    }
+
+    /// Short name of the strategy, for instrumentation.
+    fn name(&self) -> &'static str {
+        type_name::<Self>().rsplit("::").next().unwrap()
+    }
+
+    /// Advance by a single decision of either ingesting or egesting, for instrumentation.
+    fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+        self.egest().map(Step::Egest)
+    }
+
+    /// Current coefficients of the strategy, for instrumentation.
+    fn coefficients(&self) -> Vec<isize> {
+        Vec::new()
+    }
+
+    /// Number of times the strategy divided its coefficients by a common factor, for instrumentation.
+    fn reductions(&self) -> usize {
+        0
+    }
+
+    /// Bounds _nmin / dmin_ and _nmax / dmax_ of the values still to be produced, for instrumentation.
+    fn image(&self) -> Vec<isize> {
+        Vec::new()
+    }
+
+    /// Clogs ingested by the strategy, for instrumentation.
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,224 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Mutex;
+use std::task::Poll;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use crate::protocol::Reduction;
+    use std::cmp::Ordering;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    fn reductions(x: Number) -> Vec<Reduction> {
+        x.unwrap_other().1.collect()
+    }
+
+    /// Symbols available from clog without waiting, and whether it ended.
+    fn poll_all(clog: &mut Clog) -> (Vec<Reduction>, bool) {
+        let mut available = Vec::new();
+        loop {
+            match clog.poll_egest() {
+                Poll::Ready(Some(reduction)) => available.push(reduction),
+                Poll::Ready(None) => return (available, true),
+                Poll::Pending => return (available, false),
+            }
+        }
+    }
+
+    #[test]
+    fn blocking() {
+        let (sender, receiver) = channel();
+        for reduction in reductions(Number::ratio(3, 11)) {
+            sender.send(reduction).unwrap();
+        }
+        drop(sender);
+        assert_eq!(
+            Number::compare(new(None, receiver), Number::ratio(3, 11)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn across_threads() {
+        let (sender, receiver) = channel();
+        let producer = thread::spawn(move || {
+            for reduction in reductions(Number::ratio(-7, 5)) {
+                sender.send(reduction).unwrap();
+            }
+        });
+        let x = new(Some(protocol::Primer::Ground), receiver);
+        assert_eq!(
+            Number::compare(Number::homographic(x, 1, 1, 0, 1), Number::ratio(-2, 5)),
+            Ordering::Equal
+        );
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn pending() {
+        let (sender, receiver) = channel();
+        let (_, mut clog) = new(None, receiver).unwrap_other();
+        assert_eq!(clog.poll_egest(), Poll::Pending);
+        sender.send(Reduction::Uncover).unwrap();
+        assert_eq!(clog.poll_egest(), Poll::Ready(Some(Reduction::Uncover)));
+        assert_eq!(clog.poll_egest(), Poll::Pending);
+        drop(sender);
+        assert_eq!(clog.poll_egest(), Poll::Ready(None));
+        assert_eq!(clog.poll_egest(), Poll::Ready(None));
+    }
+
+    /// Feed the symbols of x to f one at a time, checking that every polled output
+    /// symbol agrees with the symbols of expected.
+    fn incremental<F>(x: Number, f: F, expected: Number)
+    where
+        F: FnOnce(Number) -> Number,
+    {
+        let (primer, mut symbols) = x.unwrap_other();
+        let (sender, receiver) = channel();
+        // Priming waits for input, so supply enough for it first.
+        for _ in 0..4 {
+            if let Some(reduction) = symbols.egest() {
+                sender.send(reduction).unwrap();
+            }
+        }
+        let (_, mut y) = f(new(primer, receiver)).unwrap_other();
+        let (expected_primer, expected) = expected.unwrap_other();
+        assert_eq!(expected_primer, None);
+        let expected: Vec<Reduction> = expected.collect();
+        let mut egested = Vec::new();
+        loop {
+            let (available, ended) = poll_all(&mut y);
+            assert!(!ended);
+            egested.extend(available);
+            assert!(expected.starts_with(&egested));
+            match symbols.egest() {
+                Some(reduction) => sender.send(reduction).unwrap(),
+                None => break,
+            }
+        }
+        // Output was produced before the input was complete.
+        assert!(!egested.is_empty());
+        drop(sender);
+        let (available, ended) = poll_all(&mut y);
+        egested.extend(available);
+        assert!(ended);
+        assert_eq!(egested, expected);
+    }
+
+    #[test]
+    fn homographic() {
+        // x / 2 follows x with a delay of one symbol.
+        incremental(
+            Number::ratio(999, 1024),
+            |x| Number::homographic(x, 1, 0, 0, 2),
+            Number::ratio(999, 2048),
+        );
+    }
+
+    #[test]
+    fn combine() {
+        // (x + y) / 3 for a known y.
+        incremental(
+            Number::ratio(200, 201),
+            |x| Number::combine(x, Number::ratio(1, 3), 0, 1, 1, 0, 0, 0, 0, 3),
+            Number::ratio(801, 1809),
+        );
+    }
+
+    #[test]
+    fn combine_streams() {
+        let x = reductions(Number::ratio(999, 1024));
+        let y = reductions(Number::ratio(100, 101));
+        let (sx, rx) = channel();
+        let (sy, ry) = channel();
+        // Priming waits for input, so supply enough for it first.
+        for i in 0..8 {
+            sx.send(x[i]).unwrap();
+            sy.send(y[i]).unwrap();
+        }
+        // (x + y) / 2
+        let (_, mut z) =
+            Number::combine(new(None, rx), new(None, ry), 0, 1, 1, 0, 0, 0, 0, 2).unwrap_other();
+        let (expected_primer, expected) = Number::ratio(203299, 206848).unwrap_other();
+        assert_eq!(expected_primer, None);
+        let expected: Vec<Reduction> = expected.collect();
+        let (mut egested, ended) = poll_all(&mut z);
+        assert!(!ended);
+        // Only y moves on: polling does not wait for x.
+        for reduction in y.iter().skip(8) {
+            sy.send(*reduction).unwrap();
+        }
+        drop(sy);
+        let (available, ended) = poll_all(&mut z);
+        assert!(!ended);
+        egested.extend(available);
+        assert!(!egested.is_empty());
+        assert!(expected.starts_with(&egested));
+        for reduction in x.iter().skip(8) {
+            sx.send(*reduction).unwrap();
+        }
+        drop(sx);
+        let (available, ended) = poll_all(&mut z);
+        assert!(ended);
+        egested.extend(available);
+        assert_eq!(egested, expected);
+    }
+}
+
+/// Reduction symbols arriving on a channel; the stream ends when the sender is dropped.
+pub struct Channel {
+    receiver: Mutex<Receiver<protocol::Reduction>>,
+}
+
+/// The Number given by an optional primer and the Reduction symbols received.
+pub fn new(primer: Option<protocol::Primer>, receiver: Receiver<protocol::Reduction>) -> Number {
+    Number::Other(
+        primer,
+        Clog {
+            strategy: Box::new(Channel {
+                receiver: Mutex::new(receiver),
+            }),
+        },
+    )
+}
+
+impl Strategy for Channel {
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        Ok(self.receiver.get_mut().unwrap().recv().ok())
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        match self.receiver.get_mut().unwrap().try_recv() {
+            Ok(reduction) => Ok(Poll::Ready(Some(reduction))),
+            Err(TryRecvError::Empty) => Ok(Poll::Pending),
+            Err(TryRecvError::Disconnected) => Ok(Poll::Ready(None)),
+        }
+    }
+}
@@ -24,16 +24,18 @@ use crate::strategy::homographic::Homographic;
 use crate::strategy::ratio;
 use crate::strategy::ratio::Ratio;
 use crate::strategy::support;
-use crate::strategy::Strategy;
+use crate::strategy::{Step, Strategy};
 use crate::Clog;
 use crate::Number;
-use std::mem::swap;
-use std::rc::Rc;
+use std::mem::{replace, swap};
+use std::task::Poll;
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 
     use super::*;
+    use std::any::Any;
     use std::cmp::Ordering;
 
     #[test]
@@ -593,6 +595,111 @@ mod tests {
         assert!(t(2, 1, 2, 1, 0, 1));
     }
 
+    /// Whether combining x and y with coefficients c gives r, all ratios given as (num, den).
+    fn combines_to(x: (isize, isize), y: (isize, isize), c: [isize; 8], r: (isize, isize)) -> bool {
+        Number::compare(
+            Number::combine(
+                Number::ratio(x.0, x.1),
+                Number::ratio(y.0, y.1),
+                c[0],
+                c[1],
+                c[2],
+                c[3],
+                c[4],
+                c[5],
+                c[6],
+                c[7],
+            ),
+            Number::ratio(r.0, r.1),
+        ) == Ordering::Equal
+    }
+
+    #[test]
+    fn zero_at_corner() {
+        // x * y and x vanish at corners of the domain, d = 0.
+        assert!(combines_to(
+            (1, 3),
+            (3, 4),
+            [1, 0, 0, 0, 0, 0, 0, 1],
+            (1, 4)
+        ));
+        assert!(combines_to(
+            (1, 3),
+            (3, 4),
+            [0, 1, 0, 0, 0, 0, 0, 1],
+            (1, 3)
+        ));
+        assert!(combines_to(
+            (1, 100),
+            (1, 100),
+            [1, 0, 0, 0, 0, 0, 0, 1],
+            (1, 10000)
+        ));
+    }
+
+    #[test]
+    fn zero_in_domain() {
+        // x - y changes sign across the diagonal of the domain.
+        let sub = [0, 1, -1, 0, 0, 0, 0, 1];
+        assert!(combines_to((1, 4), (2, 3), sub, (-5, 12)));
+        assert!(combines_to((2, 3), (1, 4), sub, (5, 12)));
+        assert!(combines_to((2, 5), (2, 5), sub, (0, 1)));
+        // (4 * x * y - 1) / 4 vanishes along a hyperbola.
+        let hyperbola = [4, 0, 0, -1, 0, 0, 0, 4];
+        assert!(combines_to((1, 2), (1, 2), hyperbola, (0, 1)));
+        assert!(combines_to((3, 4), (2, 3), hyperbola, (1, 4)));
+        assert!(combines_to((1, 5), (1, 2), hyperbola, (-3, 20)));
+    }
+
+    #[test]
+    fn pole_at_corner() {
+        // 1 / (x + y) and 1 / x have poles at corners of the domain, h = 0.
+        assert!(combines_to(
+            (1, 3),
+            (1, 6),
+            [0, 0, 0, 1, 0, 1, 1, 0],
+            (2, 1)
+        ));
+        assert!(combines_to(
+            (1, 3),
+            (1, 6),
+            [0, 0, 0, 1, 0, 1, 0, 0],
+            (3, 1)
+        ));
+        assert!(combines_to(
+            (1, 100),
+            (1, 100),
+            [0, 0, 0, 1, 1, 0, 0, 0],
+            (10000, 1)
+        ));
+    }
+
+    #[test]
+    fn pole_in_domain() {
+        // 1 / (x - y) has a pole along the diagonal of the domain.
+        let recip_sub = [0, 0, 0, 1, 0, 1, -1, 0];
+        assert!(combines_to((1, 4), (2, 3), recip_sub, (-12, 5)));
+        assert!(combines_to((2, 3), (1, 4), recip_sub, (12, 5)));
+        assert!(combines_to((1, 2), (499, 1000), recip_sub, (1000, 1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn pole_hit() {
+        combines_to((1, 3), (1, 3), [0, 0, 0, 1, 0, 1, -1, 0], (1, 1));
+    }
+
+    #[test]
+    fn zero_and_pole_in_domain() {
+        // (x - y) / (x + y - 1) has both a zero and a pole crossing the domain.
+        let c = [0, 1, -1, 0, 0, 1, 1, -1];
+        assert!(combines_to((1, 4), (2, 3), c, (5, 1)));
+        assert!(combines_to((3, 4), (1, 3), c, (5, 1)));
+        assert!(combines_to((1, 5), (1, 2), c, (1, 1)));
+        assert!(combines_to((1, 3), (1, 3), c, (0, 1)));
+        assert!(combines_to((1, 8), (1, 4), c, (1, 5)));
+    }
+
     #[test]
     fn egest0() {
         assert_eq!(
@@ -614,11 +721,107 @@ mod tests {
             Ordering::Equal
         );
     }
+
+    #[test]
+    fn long_prime() {
+        // x * y / (x * y) is one, and amplify ingestions leave its coefficients unchanged.
+        assert_eq!(
+            Number::compare(
+                Number::combine(
+                    crate::strategy::tests::amplified(300_000),
+                    crate::strategy::tests::amplified(300_000),
+                    1,
+                    0,
+                    0,
+                    0,
+                    1,
+                    0,
+                    0,
+                    0
+                ),
+                Number::ratio(1, 1)
+            ),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn long_egest() {
+        // Past its primer, x / (x + y) over equal inputs is one half, but stays undecided
+        // until an input ends, and amplify ingestions from both inputs leave its coefficients unchanged.
+        let (_, x) = crate::strategy::tests::amplified(300_000).unwrap_other();
+        let (_, y) = crate::strategy::tests::amplified(300_000).unwrap_other();
+        let mut clog = Clog {
+            strategy: Box::new(Combine {
+                x,
+                y,
+                a: 0,
+                b: 1,
+                c: 0,
+                d: 0,
+                e: 0,
+                f: 1,
+                g: 1,
+                h: 0,
+                ingested: 0,
+                reductions: 0,
+            }),
+        };
+        let profile = clog.profile();
+        assert_eq!(clog.egest(), None);
+        assert_eq!(profile.stats().ingested, vec![300_000, 300_000]);
+    }
+
+    #[test]
+    fn reduces_common_factor() {
+        let k = 7isize.pow(20);
+        assert_eq!(
+            Number::compare(
+                Number::combine(
+                    Number::ratio(-123, 457),
+                    Number::ratio(1001, 37),
+                    k,
+                    2 * k,
+                    0,
+                    -k,
+                    0,
+                    0,
+                    3 * k,
+                    k
+                ),
+                Number::ratio(-123 * 1001 - 2 * 123 * 37 - 457 * 37, 457 * (3 * 1001 + 37))
+            ),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn fuses_homographic_inputs() {
+        fn x() -> Number {
+            Number::homographic(Number::ratio(1, 1000), 1, 1, 0, 1)
+        }
+        fn y() -> Number {
+            Number::homographic(Number::ratio(1, 3), -1, 0, 0, 1)
+        }
+        fn z() -> Number {
+            Number::combine(x(), y(), 1, 0, 0, 0, 0, 0, 0, 1)
+        }
+        let (_, clog) = z().unwrap_other();
+        let combine = (&*clog.strategy as &dyn Any)
+            .downcast_ref::<Combine>()
+            .unwrap();
+        assert!(!(&*combine.x.strategy as &dyn Any).is::<Homographic>());
+        assert!(!(&*combine.y.strategy as &dyn Any).is::<Homographic>());
+        assert_eq!(
+            Number::compare(z(), Number::ratio(-1001, 3000)),
+            Ordering::Equal
+        );
+    }
 }
 
 pub struct Combine {
-    x: Rc<Clog>,
-    y: Rc<Clog>,
+    x: Clog,
+    y: Clog,
     a: isize,
     b: isize,
     c: isize,
@@ -627,6 +830,8 @@ pub struct Combine {
     f: isize,
     g: isize,
     h: isize,
+    ingested: usize,
+    reductions: usize,
 }
 
 /*
@@ -672,6 +877,7 @@ poles in domain
 signums: h, f+h, g+h, e+f+g+h
  */
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn new(
     x: Number,
     y: Number,
@@ -727,19 +933,19 @@ pub fn new(
 
     macro_rules! reflect_x {
         () => {
-            a *= -1;
-            b *= -1;
-            e *= -1;
-            f *= -1;
+            a = a.checked_neg().expect(support::OVERFLOW);
+            b = b.checked_neg().expect(support::OVERFLOW);
+            e = e.checked_neg().expect(support::OVERFLOW);
+            f = f.checked_neg().expect(support::OVERFLOW);
         };
     }
 
     macro_rules! reflect_y {
         () => {
-            a *= -1;
-            c *= -1;
-            e *= -1;
-            g *= -1;
+            a = a.checked_neg().expect(support::OVERFLOW);
+            c = c.checked_neg().expect(support::OVERFLOW);
+            e = e.checked_neg().expect(support::OVERFLOW);
+            g = g.checked_neg().expect(support::OVERFLOW);
         };
     }
 
@@ -757,40 +963,60 @@ pub fn new(
         };
     }
 
+    // Absorb Homographic strategies driving the inputs.
+    let mut x = x;
+    let mut y = y;
+    let mut m = [a, b, c, d, e, f, g, h];
+    if let Some(mx) = homographic::peek(&x) {
+        let fused = support::substitute_x(m, mx);
+        if support::are_within(&fused, homographic::FUSION_LIMIT) {
+            m = fused;
+            x = homographic::unwrap(x);
+        }
+    }
+    if let Some(my) = homographic::peek(&y) {
+        let fused = support::substitute_y(m, my);
+        if support::are_within(&fused, homographic::FUSION_LIMIT) {
+            m = fused;
+            y = homographic::unwrap(y);
+        }
+    }
+    [a, b, c, d, e, f, g, h] = m;
+
     if let Number::Special(special) = x {
         match special {
             protocol::Special::NegOne => as_homographic(
                 y,
-                c.checked_sub(a).unwrap(),
-                d.checked_sub(b).unwrap(),
-                g.checked_sub(e).unwrap(),
-                h.checked_sub(f).unwrap(),
+                c.checked_sub(a).expect(support::OVERFLOW),
+                d.checked_sub(b).expect(support::OVERFLOW),
+                g.checked_sub(e).expect(support::OVERFLOW),
+                h.checked_sub(f).expect(support::OVERFLOW),
             ),
             protocol::Special::Zero => as_homographic(y, c, d, g, h),
             protocol::Special::PosOne => as_homographic(
                 y,
-                c.checked_add(a).unwrap(),
-                d.checked_add(b).unwrap(),
-                g.checked_add(e).unwrap(),
-                h.checked_add(f).unwrap(),
+                c.checked_add(a).expect(support::OVERFLOW),
+                d.checked_add(b).expect(support::OVERFLOW),
+                g.checked_add(e).expect(support::OVERFLOW),
+                h.checked_add(f).expect(support::OVERFLOW),
             ),
         }
     } else if let Number::Special(special) = y {
         match special {
             protocol::Special::NegOne => as_homographic(
                 x,
-                b.checked_sub(a).unwrap(),
-                d.checked_sub(c).unwrap(),
-                f.checked_sub(e).unwrap(),
-                h.checked_sub(g).unwrap(),
+                b.checked_sub(a).expect(support::OVERFLOW),
+                d.checked_sub(c).expect(support::OVERFLOW),
+                f.checked_sub(e).expect(support::OVERFLOW),
+                h.checked_sub(g).expect(support::OVERFLOW),
             ),
             protocol::Special::Zero => as_homographic(x, b, d, f, h),
             protocol::Special::PosOne => as_homographic(
                 x,
-                b.checked_add(a).unwrap(),
-                d.checked_add(c).unwrap(),
-                f.checked_add(e).unwrap(),
-                h.checked_add(g).unwrap(),
+                b.checked_add(a).expect(support::OVERFLOW),
+                d.checked_add(c).expect(support::OVERFLOW),
+                f.checked_add(e).expect(support::OVERFLOW),
+                h.checked_add(g).expect(support::OVERFLOW),
             ),
         }
     } else {
@@ -822,14 +1048,15 @@ pub fn new(
             None => {}
         }
 
-        Combine::new(Rc::new(x_clog), Rc::new(y_clog), a, b, c, d, e, f, g, h)
+        Combine::new(x_clog, y_clog, a, b, c, d, e, f, g, h)
     }
 }
 
 impl Combine {
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     fn new(
-        x: Rc<Clog>,
-        y: Rc<Clog>,
+        x: Clog,
+        y: Clog,
         a: isize,
         b: isize,
         c: isize,
@@ -845,7 +1072,7 @@ impl Combine {
         Option<Homographic>,
         Option<Combine>,
     ) {
-        (Combine {
+        let mut combine = Combine {
             x,
             y,
             a,
@@ -856,10 +1083,14 @@ impl Combine {
             f,
             g,
             h,
-        })
-        .prime()
+            ingested: 0,
+            reductions: 0,
+        };
+        combine.reduce();
+        combine.prime()
     }
 
+    #[allow(clippy::type_complexity)]
     fn prime(
         mut self,
     ) -> (
@@ -869,16 +1100,18 @@ impl Combine {
         Option<Homographic>,
         Option<Combine>,
     ) {
-        if self.are_singularities_outside_domain() {
-            if let Ok(primer) = self.primer_egest() {
-                return (None, primer, None, None, Some(self));
+        loop {
+            if self.are_singularities_outside_domain() {
+                if let Ok(primer) = self.primer_egest() {
+                    return (None, primer, None, None, Some(self));
+                }
             }
-        }
-        match self.prime_ingest() {
-            Ok((special, primer, ratio, homographic)) => {
-                (special, primer, ratio, homographic, None)
+            match self.prime_ingest() {
+                Ok((special, primer, ratio, homographic)) => {
+                    return (special, primer, ratio, homographic, None);
+                }
+                Err(myself) => self = myself,
             }
-            Err(myself) => myself.prime(),
         }
     }
 
@@ -897,11 +1130,14 @@ impl Combine {
     fn is_domain_amenable(mxy: isize, mx: isize, my: isize, m: isize) -> bool {
         let s = m.signum();
         s != 0
-            && s == m.checked_add(mx).unwrap().signum()
-            && s == m.checked_add(my).unwrap().signum()
+            && s == m.checked_add(mx).expect(support::OVERFLOW).signum()
+            && s == m.checked_add(my).expect(support::OVERFLOW).signum()
             && s == m
-                .checked_add(mxy.checked_add(mx.checked_add(my).unwrap()).unwrap())
-                .unwrap()
+                .checked_add(
+                    mxy.checked_add(mx.checked_add(my).expect(support::OVERFLOW))
+                        .expect(support::OVERFLOW),
+                )
+                .expect(support::OVERFLOW)
                 .signum()
     }
 
@@ -921,6 +1157,7 @@ impl Combine {
         }
     }
 
+    #[allow(clippy::type_complexity)]
     fn prime_ingest(
         mut self,
     ) -> Result<
@@ -932,16 +1169,10 @@ impl Combine {
         ),
         Combine,
     > {
-        match Rc::get_mut(&mut self.x).unwrap().egest() {
+        match self.x.egest() {
             None => {
                 let (ny, n, dy, d) = self.value_at_end_of_x();
-                return Ok(homographic::new(
-                    Number::Other(None, Rc::try_unwrap(self.y).ok().unwrap()),
-                    ny,
-                    n,
-                    dy,
-                    d,
-                ));
+                return Ok(homographic::new(Number::Other(None, self.y), ny, n, dy, d));
             }
             Some(protocol::Reduction::Amplify) => {
                 self.amplify_x();
@@ -950,16 +1181,10 @@ impl Combine {
                 self.uncover_x();
             }
         }
-        match Rc::get_mut(&mut self.y).unwrap().egest() {
+        match self.y.egest() {
             None => {
                 let (nx, n, dx, d) = self.value_at_end_of_y();
-                return Ok(homographic::new(
-                    Number::Other(None, Rc::try_unwrap(self.x).ok().unwrap()),
-                    nx,
-                    n,
-                    dx,
-                    d,
-                ));
+                return Ok(homographic::new(Number::Other(None, self.x), nx, n, dx, d));
             }
             Some(protocol::Reduction::Amplify) => {
                 self.amplify_y();
@@ -978,17 +1203,25 @@ impl Combine {
             && support::is_even(self.f)
         {
             (
-                self.c.checked_add(self.a / 2).unwrap(),
-                self.d.checked_add(self.b / 2).unwrap(),
-                self.g.checked_add(self.e / 2).unwrap(),
-                self.h.checked_add(self.f / 2).unwrap(),
+                self.c.checked_add(self.a / 2).expect(support::OVERFLOW),
+                self.d.checked_add(self.b / 2).expect(support::OVERFLOW),
+                self.g.checked_add(self.e / 2).expect(support::OVERFLOW),
+                self.h.checked_add(self.f / 2).expect(support::OVERFLOW),
             )
         } else {
             (
-                self.a.checked_add(self.c.checked_mul(2).unwrap()).unwrap(),
-                self.b.checked_add(self.d.checked_mul(2).unwrap()).unwrap(),
-                self.e.checked_add(self.g.checked_mul(2).unwrap()).unwrap(),
-                self.f.checked_add(self.h.checked_mul(2).unwrap()).unwrap(),
+                self.a
+                    .checked_add(self.c.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.b
+                    .checked_add(self.d.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.e
+                    .checked_add(self.g.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.f
+                    .checked_add(self.h.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
             )
         }
     }
@@ -1000,17 +1233,25 @@ impl Combine {
             && support::is_even(self.g)
         {
             (
-                self.b.checked_add(self.a / 2).unwrap(),
-                self.d.checked_add(self.c / 2).unwrap(),
-                self.f.checked_add(self.e / 2).unwrap(),
-                self.h.checked_add(self.g / 2).unwrap(),
+                self.b.checked_add(self.a / 2).expect(support::OVERFLOW),
+                self.d.checked_add(self.c / 2).expect(support::OVERFLOW),
+                self.f.checked_add(self.e / 2).expect(support::OVERFLOW),
+                self.h.checked_add(self.g / 2).expect(support::OVERFLOW),
             )
         } else {
             (
-                self.a.checked_add(self.b.checked_mul(2).unwrap()).unwrap(),
-                self.c.checked_add(self.d.checked_mul(2).unwrap()).unwrap(),
-                self.e.checked_add(self.f.checked_mul(2).unwrap()).unwrap(),
-                self.g.checked_add(self.h.checked_mul(2).unwrap()).unwrap(),
+                self.a
+                    .checked_add(self.b.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.c
+                    .checked_add(self.d.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.e
+                    .checked_add(self.f.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.g
+                    .checked_add(self.h.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
             )
         }
     }
@@ -1026,11 +1267,12 @@ impl Combine {
             self.e /= 2;
             self.f /= 2;
         } else {
-            self.c = self.c.checked_mul(2).unwrap();
-            self.d = self.d.checked_mul(2).unwrap();
-            self.g = self.g.checked_mul(2).unwrap();
-            self.h = self.h.checked_mul(2).unwrap();
+            self.c = self.c.checked_mul(2).expect(support::OVERFLOW);
+            self.d = self.d.checked_mul(2).expect(support::OVERFLOW);
+            self.g = self.g.checked_mul(2).expect(support::OVERFLOW);
+            self.h = self.h.checked_mul(2).expect(support::OVERFLOW);
         }
+        self.count_ingestion();
     }
 
     fn amplify_y(&mut self) {
@@ -1044,21 +1286,44 @@ impl Combine {
             self.e /= 2;
             self.g /= 2;
         } else {
-            self.b = self.b.checked_mul(2).unwrap();
-            self.d = self.d.checked_mul(2).unwrap();
-            self.f = self.f.checked_mul(2).unwrap();
-            self.h = self.h.checked_mul(2).unwrap();
+            self.b = self.b.checked_mul(2).expect(support::OVERFLOW);
+            self.d = self.d.checked_mul(2).expect(support::OVERFLOW);
+            self.f = self.f.checked_mul(2).expect(support::OVERFLOW);
+            self.h = self.h.checked_mul(2).expect(support::OVERFLOW);
         }
+        self.count_ingestion();
     }
 
     fn uncover_x(&mut self) {
         self.turn_x();
         self.shift_x();
+        self.count_ingestion();
     }
 
     fn uncover_y(&mut self) {
         self.turn_y();
         self.shift_y();
+        self.count_ingestion();
+    }
+
+    /// Count one ingestion, periodically dividing coefficients by their gcd.
+    fn count_ingestion(&mut self) {
+        self.ingested += 1;
+        if self.ingested.is_multiple_of(support::REDUCTION_PERIOD) {
+            self.reduce();
+        }
+    }
+
+    fn reduce(&mut self) {
+        let mut m = [
+            self.a, self.b, self.c, self.d, self.e, self.f, self.g, self.h,
+        ];
+        if support::reduce(&mut m) {
+            self.reductions += 1;
+        }
+        [
+            self.a, self.b, self.c, self.d, self.e, self.f, self.g, self.h,
+        ] = m;
     }
 
     fn turn_x(&mut self) {
@@ -1076,17 +1341,17 @@ impl Combine {
     }
 
     fn shift_x(&mut self) {
-        self.c = self.c.checked_add(self.a).unwrap();
-        self.d = self.d.checked_add(self.b).unwrap();
-        self.g = self.g.checked_add(self.e).unwrap();
-        self.h = self.h.checked_add(self.f).unwrap();
+        self.c = self.c.checked_add(self.a).expect(support::OVERFLOW);
+        self.d = self.d.checked_add(self.b).expect(support::OVERFLOW);
+        self.g = self.g.checked_add(self.e).expect(support::OVERFLOW);
+        self.h = self.h.checked_add(self.f).expect(support::OVERFLOW);
     }
 
     fn shift_y(&mut self) {
-        self.b = self.b.checked_add(self.a).unwrap();
-        self.d = self.d.checked_add(self.c).unwrap();
-        self.f = self.f.checked_add(self.e).unwrap();
-        self.h = self.h.checked_add(self.g).unwrap();
+        self.b = self.b.checked_add(self.a).expect(support::OVERFLOW);
+        self.d = self.d.checked_add(self.c).expect(support::OVERFLOW);
+        self.f = self.f.checked_add(self.e).expect(support::OVERFLOW);
+        self.h = self.h.checked_add(self.g).expect(support::OVERFLOW);
     }
 
     fn image_extremes(&self) -> (isize, isize, isize, isize) {
@@ -1106,10 +1371,10 @@ impl Combine {
     }
 
     fn reflect(&mut self) -> protocol::Primer {
-        self.a = -self.a;
-        self.b = -self.b;
-        self.c = -self.c;
-        self.d = -self.d;
+        self.a = self.a.checked_neg().expect(support::OVERFLOW);
+        self.b = self.b.checked_neg().expect(support::OVERFLOW);
+        self.c = self.c.checked_neg().expect(support::OVERFLOW);
+        self.d = self.d.checked_neg().expect(support::OVERFLOW);
         protocol::Primer::Reflect
     }
 
@@ -1127,15 +1392,15 @@ impl Combine {
 
     fn value_at_0_1(&self) -> (isize, isize) {
         (
-            self.c.checked_add(self.d).unwrap(),
-            self.g.checked_add(self.h).unwrap(),
+            self.c.checked_add(self.d).expect(support::OVERFLOW),
+            self.g.checked_add(self.h).expect(support::OVERFLOW),
         )
     }
 
     fn value_at_1_0(&self) -> (isize, isize) {
         (
-            self.b.checked_add(self.d).unwrap(),
-            self.f.checked_add(self.h).unwrap(),
+            self.b.checked_add(self.d).expect(support::OVERFLOW),
+            self.f.checked_add(self.h).expect(support::OVERFLOW),
         )
     }
 
@@ -1144,17 +1409,17 @@ impl Combine {
             self.a
                 .checked_add(
                     self.b
-                        .checked_add(self.c.checked_add(self.d).unwrap())
-                        .unwrap(),
+                        .checked_add(self.c.checked_add(self.d).expect(support::OVERFLOW))
+                        .expect(support::OVERFLOW),
                 )
-                .unwrap(),
+                .expect(support::OVERFLOW),
             self.e
                 .checked_add(
                     self.f
-                        .checked_add(self.g.checked_add(self.h).unwrap())
-                        .unwrap(),
+                        .checked_add(self.g.checked_add(self.h).expect(support::OVERFLOW))
+                        .expect(support::OVERFLOW),
                 )
-                .unwrap(),
+                .expect(support::OVERFLOW),
         )
     }
 
@@ -1187,10 +1452,10 @@ impl Combine {
             self.g /= 2;
             self.h /= 2;
         } else {
-            self.a = self.a.checked_mul(2).unwrap();
-            self.b = self.b.checked_mul(2).unwrap();
-            self.c = self.c.checked_mul(2).unwrap();
-            self.d = self.d.checked_mul(2).unwrap();
+            self.a = self.a.checked_mul(2).expect(support::OVERFLOW);
+            self.b = self.b.checked_mul(2).expect(support::OVERFLOW);
+            self.c = self.c.checked_mul(2).expect(support::OVERFLOW);
+            self.d = self.d.checked_mul(2).expect(support::OVERFLOW);
         }
         protocol::Reduction::Amplify
     }
@@ -1202,89 +1467,158 @@ impl Combine {
     }
 
     fn shift(&mut self) {
-        self.a = self.a.checked_sub(self.e).unwrap();
-        self.b = self.b.checked_sub(self.f).unwrap();
-        self.c = self.c.checked_sub(self.g).unwrap();
-        self.d = self.d.checked_sub(self.h).unwrap();
+        self.a = self.a.checked_sub(self.e).expect(support::OVERFLOW);
+        self.b = self.b.checked_sub(self.f).expect(support::OVERFLOW);
+        self.c = self.c.checked_sub(self.g).expect(support::OVERFLOW);
+        self.d = self.d.checked_sub(self.h).expect(support::OVERFLOW);
     }
 
-    fn simple_clog() -> Rc<Clog> {
+    fn simple_clog() -> Clog {
         let (_, _, ratio) = ratio::new_i(1, 2);
-        Rc::new(Clog {
+        Clog {
             strategy: Box::new(ratio.unwrap()),
-        })
-    }
-
-    fn promote_clog(clog: Rc<Clog>) -> Number {
-        Number::Other(None, Rc::try_unwrap(clog).ok().unwrap())
+        }
     }
 
-    fn shutdown_x(&mut self) -> Number {
-        let moved = Rc::clone(&self.x);
-        self.x = Combine::simple_clog();
-        Combine::promote_clog(moved)
+    fn shutdown_x(&mut self) -> Clog {
+        replace(&mut self.x, Combine::simple_clog())
     }
 
-    fn shutdown_y(&mut self) -> Number {
-        let moved = Rc::clone(&self.y);
-        self.y = Combine::simple_clog();
-        Combine::promote_clog(moved)
+    fn shutdown_y(&mut self) -> Clog {
+        replace(&mut self.y, Combine::simple_clog())
     }
 
-    fn reduction_ingest(&mut self) -> (Option<Ratio>, Option<Homographic>) {
-        match Rc::get_mut(&mut self.x).unwrap().egest() {
-            None => {
+    /**
+     * Take a symbol from each input with ingest.
+     *
+     * Pending only if neither input has a symbol available.
+     */
+    fn reduction_ingest<F>(&mut self, ingest: &mut F) -> Poll<Option<Homographic>>
+    where
+        F: FnMut(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        let mut pending = false;
+        match ingest(&mut self.x) {
+            Poll::Pending => {
+                pending = true;
+            }
+            Poll::Ready(None) => {
+                // The image of the remaining input lies within the image of self,
+                // which priming has already brought into the domain.
                 let (ny, n, dy, d) = self.value_at_end_of_x();
-                match homographic::new(self.shutdown_y(), ny, n, dy, d) {
-                    (None, None, ratio, homographic) => {
-                        return (ratio, homographic);
-                    }
-                    _ => {
-                        panic!("logic error");
-                    }
-                }
+                return Poll::Ready(Some(homographic::new_primed(
+                    self.shutdown_y(),
+                    ny,
+                    n,
+                    dy,
+                    d,
+                )));
             }
-            Some(protocol::Reduction::Amplify) => {
+            Poll::Ready(Some(protocol::Reduction::Amplify)) => {
                 self.amplify_x();
             }
-            Some(protocol::Reduction::Uncover) => {
+            Poll::Ready(Some(protocol::Reduction::Uncover)) => {
                 self.uncover_x();
             }
         }
-        match Rc::get_mut(&mut self.y).unwrap().egest() {
-            None => {
-                let (nx, n, dx, d) = self.value_at_end_of_y();
-                match homographic::new(self.shutdown_x(), nx, n, dx, d) {
-                    (None, None, ratio, homographic) => {
-                        return (ratio, homographic);
-                    }
-                    _ => {
-                        panic!("logic error");
-                    }
+        match ingest(&mut self.y) {
+            Poll::Pending => {
+                if pending {
+                    return Poll::Pending;
                 }
             }
-            Some(protocol::Reduction::Amplify) => {
+            Poll::Ready(None) => {
+                // The image of the remaining input lies within the image of self,
+                // which priming has already brought into the domain.
+                let (nx, n, dx, d) = self.value_at_end_of_y();
+                return Poll::Ready(Some(homographic::new_primed(
+                    self.shutdown_x(),
+                    nx,
+                    n,
+                    dx,
+                    d,
+                )));
+            }
+            Poll::Ready(Some(protocol::Reduction::Amplify)) => {
                 self.amplify_y();
             }
-            Some(protocol::Reduction::Uncover) => {
+            Poll::Ready(Some(protocol::Reduction::Uncover)) => {
                 self.uncover_y();
             }
         }
-        (None, None)
+        Poll::Ready(None)
     }
-}
 
-impl Strategy for Combine {
-    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+    /// Take a single decision, ingesting input symbols with ingest if no symbol is determined.
+    fn step_with<F>(&mut self, ingest: &mut F) -> Result<Poll<Step>, Box<dyn Strategy>>
+    where
+        F: FnMut(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
         if self.are_singularities_outside_domain() {
             if let Ok(reduction) = self.reduction_egest() {
-                return Ok(reduction);
+                return Ok(Poll::Ready(Step::Egest(reduction)));
             }
         }
-        match self.reduction_ingest() {
-            (_, Some(homographic)) => Err(Box::new(homographic)),
-            (Some(ratio), _) => Err(Box::new(ratio)),
-            _ => self.egest(),
+        match self.reduction_ingest(ingest) {
+            Poll::Pending => Ok(Poll::Pending),
+            Poll::Ready(Some(homographic)) => Err(Box::new(homographic)),
+            Poll::Ready(None) => Ok(Poll::Ready(Step::Ingest)),
         }
     }
+
+    /// Egest a symbol, taking input symbols with ingest until one is determined.
+    fn egest_with<F>(
+        &mut self,
+        mut ingest: F,
+    ) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>>
+    where
+        F: FnMut(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        loop {
+            match self.step_with(&mut ingest)? {
+                Poll::Ready(Step::Egest(reduction)) => return Ok(Poll::Ready(reduction)),
+                Poll::Ready(Step::Ingest) => {}
+                Poll::Pending => return Ok(Poll::Pending),
+            }
+        }
+    }
+}
+
+impl Strategy for Combine {
+    fn coefficients(&self) -> Vec<isize> {
+        vec![
+            self.a, self.b, self.c, self.d, self.e, self.f, self.g, self.h,
+        ]
+    }
+
+    fn reductions(&self) -> usize {
+        self.reductions
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        vec![&mut self.x, &mut self.y]
+    }
+
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        match self.egest_with(|x| Poll::Ready(x.egest()))? {
+            Poll::Ready(reduction) => Ok(reduction),
+            Poll::Pending => panic!("logic error"),
+        }
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        self.egest_with(Clog::poll_egest)
+    }
+
+    fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+        match self.step_with(&mut |x: &mut Clog| Poll::Ready(x.egest()))? {
+            Poll::Ready(step) => Ok(step),
+            Poll::Pending => panic!("logic error"),
+        }
+    }
+
+    fn image(&self) -> Vec<isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        vec![nmin, dmin, nmax, dmax]
+    }
 }
@@ -18,20 +18,25 @@
  * along with clarith.  If not, see <http://www.gnu.org/licenses/>
  */
 
+use crate::bigint::BigInt;
 use crate::protocol;
-// use crate::strategy::ratio;
-// use crate::strategy::ratio::Ratio;
-// use crate::strategy::support;
-use crate::strategy::Strategy;
+use crate::strategy::ratio;
+use crate::strategy::ratio::Ratio;
+use crate::strategy::support;
+use crate::strategy::{Step, Strategy};
 use crate::Clog;
 use crate::Number;
+use std::cmp::Ordering;
 use std::mem::swap;
+use std::task::Poll;
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 
     use super::*;
     use std::cmp::Ordering;
+    use std::iter;
 
     #[test]
     fn t_none() {
@@ -48,6 +53,7 @@ mod tests {
 
     #[test]
     #[ignore] // FIXME: issue #2
+    #[allow(clippy::legacy_numeric_constants)]
     fn t_some() {
         let (xo, nx, n, dx, d) = Number::consume(Number::homographic(
             Number::ratio(isize::max_value(), 1),
@@ -71,308 +77,965 @@ mod tests {
             Ordering::Equal
         );
     }
-}
-
-pub struct Consume {
-    x: Clog,
-    nx: isize,
-    n: isize,
-    dx: isize,
-    d: isize,
-}
 
-pub fn new(x: Number) -> (Option<Clog>, isize, isize, isize, isize) {
-    if let Number::Special(s) = x {
-        return match s {
-            protocol::Special::NegOne => (None, 0, -1, 0, 1),
-            protocol::Special::Zero => (None, 0, 0, 0, 1),
-            protocol::Special::PosOne => (None, 0, 1, 0, 1),
-        };
-    }
-    let mut nx = 1;
-    let mut n = 0;
-    let mut dx = 0;
-    let mut d = 1;
-    let (po, c) = x.unwrap_other();
-    match po {
-        Some(protocol::Primer::Turn) => {
-            swap(&mut nx, &mut n);
-            swap(&mut dx, &mut d);
-        }
-        Some(protocol::Primer::Reflect) => {
-            nx = -nx;
-            dx = -dx;
-        }
-        Some(protocol::Primer::Ground) => {
-            nx = -nx;
-            dx = -dx;
-            swap(&mut nx, &mut n);
-            swap(&mut dx, &mut d);
-        }
-        None => {}
-    }
-    Consume::new(c, nx, n, dx, d)
-}
-
-impl Consume {
-    fn new(
-        x: Clog,
-        nx: isize,
-        n: isize,
-        dx: isize,
-        d: isize,
-    ) -> (Option<Clog>, isize, isize, isize, isize) {
-        (Consume { x, nx, n, dx, d }).consume()
+    /// Pseudorandom symbols.
+    fn symbols(len: usize) -> Vec<protocol::Reduction> {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        iter::repeat_with(move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            if state.is_multiple_of(3) {
+                protocol::Reduction::Amplify
+            } else {
+                protocol::Reduction::Uncover
+            }
+        })
+        .take(len)
+        .collect()
     }
 
-    fn consume(mut self) -> (Option<Clog>, isize, isize, isize, isize) {
-        loop {
-            match self.x.egest() {
-                Some(protocol::Reduction::Amplify) => {
-                    self.amplify_x();
-                }
-                Some(protocol::Reduction::Uncover) => {
-                    self.uncover_x();
+    /// Numerator and denominator of a sequence of reductions.
+    fn value(reductions: &[protocol::Reduction]) -> (BigInt, BigInt) {
+        let (mut num, mut den) = (BigInt::from(1isize), BigInt::from(2isize));
+        for reduction in reductions.iter().rev() {
+            match reduction {
+                protocol::Reduction::Amplify => den = &den << 1,
+                protocol::Reduction::Uncover => {
+                    let sum = &num + &den;
+                    num = den;
+                    den = sum;
                 }
-                None => {
-                    return (None, self.nx, self.n, self.dx, self.d);
-                }
-            }
-            if self.is_state_unsafe() {
-                return (Some(self.x), self.nx, self.n, self.dx, self.d);
             }
         }
+        (num, den)
     }
 
-    fn amplify_x(&mut self) {
-        if self.nx % 2 != 0 || self.dx % 2 != 0 {
-            self.n *= 2;
-            self.d *= 2;
-        } else {
-            self.nx /= 2;
-            self.dx /= 2;
+    /// Whether (nx * y + n) / (dx * y + d) is num / den.
+    fn maps_to(
+        (nx, n, dx, d): (&BigInt, &BigInt, &BigInt, &BigInt),
+        (yn, yd): (BigInt, BigInt),
+        (num, den): (BigInt, BigInt),
+    ) -> bool {
+        let z_num = &(nx * &yn) + &(n * &yd);
+        let z_den = &(dx * &yn) + &(d * &yd);
+        &z_num * &den == &z_den * &num
+    }
+
+    fn one_half() -> (BigInt, BigInt) {
+        (BigInt::from(1isize), BigInt::from(2isize))
+    }
+
+    #[test]
+    fn unbounded() {
+        // Far more symbols than isize coefficients can take.
+        let symbols = symbols(2000);
+        let mut consume = Consume::new(Number::from_symbols(None, symbols.clone()));
+        assert_eq!(consume.consume(5000), 2000);
+        assert!(consume.is_exhausted());
+        assert_eq!(consume.ingested(), 2000);
+        assert!(consume.state().0.to_isize().is_none());
+        assert!(maps_to(consume.state(), one_half(), value(&symbols)));
+    }
+
+    #[test]
+    fn primers() {
+        for (num, den) in [(-7, 3), (-2, 5), (17, 4), (3, 8)].iter() {
+            let (rest, nx, n, dx, d) =
+                Number::consume_unbounded(Number::ratio(*num, *den), usize::MAX);
+            assert!(rest.is_none());
+            assert!(maps_to(
+                (&nx, &n, &dx, &d),
+                one_half(),
+                (BigInt::from(*num), BigInt::from(*den))
+            ));
         }
     }
 
-    fn uncover_x(&mut self) {
-        self.nx += self.n;
-        self.dx += self.d;
-        swap(&mut self.nx, &mut self.n);
-        swap(&mut self.dx, &mut self.d);
+    #[test]
+    fn specials() {
+        let mut consume = Consume::new(Number::ratio(-1, 1));
+        assert_eq!(consume.consume(10), 0);
+        assert!(consume.is_exhausted());
+        assert!(maps_to(
+            consume.state(),
+            one_half(),
+            (BigInt::from(-1isize), BigInt::from(1isize))
+        ));
     }
 
-    fn is_state_unsafe(&self) -> bool {
-        let lim = isize::max_value() / 4;
-        self.nx.abs() >= lim || self.n.abs() >= lim || self.dx.abs() >= lim || self.d.abs() >= lim
+    #[test]
+    fn resumes() {
+        let symbols = symbols(300);
+        let mut consume = Consume::new(Number::from_symbols(
+            Some(protocol::Primer::Ground),
+            symbols.clone(),
+        ));
+        assert_eq!(consume.consume(0), 0);
+        assert_eq!(consume.consume(100), 100);
+        assert_eq!(consume.consume(100), 100);
+        assert!(!consume.is_exhausted());
+        let (num, den) = value(&symbols);
+        let (rest, nx, n, dx, d) = consume.into_parts();
+        // What remains makes up the consumed Number through the coefficients.
+        let (primer, rest) = rest.unwrap().unwrap_other();
+        assert_eq!(primer, None);
+        let rest: Vec<protocol::Reduction> = rest.collect();
+        assert_eq!(rest, &symbols[200..]);
+        assert!(maps_to((&nx, &n, &dx, &d), value(&rest), (-den, num)));
     }
 
-    /*
+    #[test]
+    fn bounded_agrees() {
+        // The isize variant stops where its coefficients are about to overflow.
+        let symbols = symbols(500);
+        let (rest, nx, n, dx, d) = Number::consume(Number::from_symbols(None, symbols.clone()));
+        let rest: Vec<protocol::Reduction> = rest.unwrap().unwrap_other().1.collect();
+        assert!(rest.len() < 500);
+        assert!([nx, n, dx, d].iter().any(|c| c.abs() >= isize::MAX / 4));
+        assert!(maps_to(
+            (
+                &BigInt::from(nx),
+                &BigInt::from(n),
+                &BigInt::from(dx),
+                &BigInt::from(d)
+            ),
+            value(&rest),
+            value(&symbols)
+        ));
+    }
 
-        fn prime_ingest(
-            &mut self,
-        ) -> Option<(
-            Option<protocol::Special>,
-            Option<protocol::Primer>,
-            Option<Ratio>,
-        )> {
-            match self.x.egest() {
-                None => {
-                    let (num, den) = self.value_at_one_half();
-                    Some(ratio::new_i(num, den))
-                }
-                Some(protocol::Reduction::Amplify) => {
-                    self.ingest_amplify();
-                    None
-                }
-                Some(protocol::Reduction::Uncover) => {
-                    self.ingest_uncover();
-                    None
-                }
-            }
+    /// Special value, primer and symbols of x.
+    fn expanded(
+        x: Number,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Vec<protocol::Reduction>,
+    ) {
+        match x {
+            Number::Special(special) => (Some(special), None, Vec::new()),
+            Number::Other(primer, clog) => (None, primer, clog.collect()),
         }
+    }
 
-        fn primer_egest(&mut self) -> Result<Option<protocol::Primer>, isize> {
-            let (nmin, dmin, nmax, dmax) = self.image_extremes();
-            if support::less_than_minus_one(nmax, dmax) {
-                Ok(Some(self.ground()))
-            } else if support::greater_than_minus_one(nmin, dmin) && support::less_than_zero(nmax, dmax)
-            {
-                Ok(Some(self.reflect()))
-            } else if support::greater_than_zero(nmin, dmin) && support::less_than_one(nmax, dmax) {
-                Ok(None)
-            } else if support::greater_than_one(nmin, dmin) {
-                Ok(Some(self.turn()))
-            } else {
-                Err(0)
+    #[test]
+    fn resumes_symbols() {
+        let symbols = symbols(300);
+        let x = || Number::from_symbols(Some(protocol::Primer::Ground), symbols.clone());
+        for budget in [0, 1, 7, 64, 299, 300, 400].iter() {
+            let mut consume = Consume::new(x());
+            consume.consume(*budget);
+            assert_eq!(expanded(consume.into_number()), expanded(x()));
+        }
+    }
+
+    #[test]
+    fn resumes_ratios() {
+        let ratios = [
+            (-1, 1),
+            (0, 1),
+            (1, 1),
+            (-7, 3),
+            (-2, 5),
+            (1, 2),
+            (17, 4),
+            (isize::MAX, 3),
+        ];
+        for (num, den) in ratios.iter() {
+            for budget in [0, 1, 2, 5, usize::MAX].iter() {
+                let (y, nx, n, dx, d) =
+                    Number::consume_unbounded(Number::ratio(*num, *den), *budget);
+                assert_eq!(
+                    expanded(Number::resume(y, nx, n, dx, d)),
+                    expanded(Number::ratio(*num, *den))
+                );
             }
         }
+    }
+
+    #[test]
+    fn resumes_bounded() {
+        // What Number::consume extracts, Number::resume puts back.
+        let symbols = symbols(500);
+        let (y, nx, n, dx, d) = Number::consume(Number::from_symbols(None, symbols.clone()));
+        let resumed = Number::resume(
+            y,
+            BigInt::from(nx),
+            BigInt::from(n),
+            BigInt::from(dx),
+            BigInt::from(d),
+        );
+        assert_eq!(
+            expanded(resumed),
+            expanded(Number::from_symbols(None, symbols))
+        );
+    }
 
-        fn ground(&mut self) -> protocol::Primer {
-            self.dx = -self.dx;
-            self.d = -self.d;
-            swap(&mut self.nx, &mut self.dx);
-            swap(&mut self.n, &mut self.d);
-            protocol::Primer::Ground
+    #[test]
+    fn resumes_transformed() {
+        // Coefficients of a transformation rather than of a consumed input.
+        let two = BigInt::from(2isize);
+        let one = BigInt::from(1isize);
+        let zero = BigInt::default();
+        let resumed = Number::resume(
+            Some(Number::ratio(1, 3)),
+            two.clone(),
+            one.clone(),
+            zero,
+            two,
+        );
+        assert_eq!(expanded(resumed), expanded(Number::ratio(5, 6)));
+    }
+
+    #[test]
+    fn resumes_beyond_isize() {
+        // Exactly one third plus 2^-100, egested as such.
+        let n = &(&BigInt::from(1isize) << 100) + &BigInt::from(3isize);
+        let d = &BigInt::from(3isize) << 100;
+        let resumed = Number::resume(
+            None,
+            BigInt::default(),
+            n.clone(),
+            BigInt::default(),
+            d.clone(),
+        );
+        let (rest, nx, m, dx, e) = Number::consume_unbounded(resumed, usize::MAX);
+        assert!(rest.is_none());
+        assert!(maps_to((&nx, &m, &dx, &e), one_half(), (n, d)));
+    }
+
+    #[test]
+    fn resumes_common_factor() {
+        // Coefficients given to resume may share any factor, not only powers of two.
+        let endless = || Number::from_symbols(None, iter::repeat(protocol::Reduction::Uncover));
+        let big = |k: isize| BigInt::from(k);
+        let consume = Consume::from_parts(Some(endless()), big(3), big(6), big(0), big(3));
+        assert_eq!(consume.state(), (&big(1), &big(2), &big(0), &big(1)));
+        assert_eq!(consume.reductions, 1);
+    }
+
+    #[test]
+    fn resumes_constants() {
+        // Transformations that do not depend on an endless input.
+        let endless = || Number::from_symbols(None, iter::repeat(protocol::Reduction::Uncover));
+        let cases = [
+            ([0, -3, 0, 3], (-1, 1)),
+            ([0, 0, 1, 5], (0, 1)),
+            ([0, 1, 0, 1], (1, 1)),
+            ([2, 2, 2, 2], (1, 1)),
+            ([-4, 6, 2, -3], (-2, 1)),
+            ([5, 0, 7, 0], (5, 7)),
+        ];
+        for ([nx, n, dx, d], (num, den)) in cases.iter() {
+            let big = |k: &isize| BigInt::from(*k);
+            let x = Number::resume(Some(endless()), big(nx), big(n), big(dx), big(d));
+            assert_eq!(expanded(x), expanded(Number::ratio(*num, *den)));
         }
+        // Constant images are recognized whatever their common factor.
+        let mut consume = Consume::new(endless());
+        consume.consume(1);
+        let one = BigInt::from(1isize);
+        consume.nx = one.clone();
+        consume.n = one.clone();
+        consume.dx = one.clone();
+        consume.d = one;
+        assert_eq!(
+            expanded(consume.into_number()),
+            expanded(Number::ratio(1, 1))
+        );
+    }
 
-        fn reflect(&mut self) -> protocol::Primer {
-            self.nx = -self.nx;
-            self.n = -self.n;
-            protocol::Primer::Reflect
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn resumes_pole() {
+        // _y / (2 * y - 1)_ at one half.
+        let one = BigInt::from(1isize);
+        Number::resume(
+            None,
+            one.clone(),
+            BigInt::default(),
+            BigInt::from(2isize),
+            -one,
+        );
+    }
+
+    #[test]
+    fn strategy() {
+        // Egests through the strategy trait, with coefficients past isize.
+        let mut consume = Consume::new(Number::from_symbols(None, symbols(2000)));
+        consume.consume(1000);
+        assert!(consume.coefficients().is_empty());
+        assert_eq!(expanded(consume.into_number()).2, symbols(2000));
+    }
+
+    #[test]
+    fn splits_ratios() {
+        let ratios = [
+            (0, 1),
+            (1, 1),
+            (-1, 1),
+            (5, 1),
+            (-5, 1),
+            (1, 3),
+            (-1, 3),
+            (7, 2),
+            (-7, 2),
+            (22, 7),
+            (-355, 113),
+            (isize::MAX, 2),
+            (isize::MIN + 1, 3),
+        ];
+        for (num, den) in ratios.iter() {
+            let (k, fraction) = Number::split_integer(Number::ratio(*num, *den));
+            assert_eq!(k, BigInt::from(num.div_euclid(*den)));
+            assert_eq!(
+                expanded(fraction),
+                expanded(Number::ratio(num.rem_euclid(*den), *den))
+            );
         }
+    }
 
-        fn turn(&mut self) -> protocol::Primer {
-            swap(&mut self.nx, &mut self.dx);
-            swap(&mut self.n, &mut self.d);
-            protocol::Primer::Turn
+    #[test]
+    fn splits_symbols() {
+        // Values beyond isize, as primers apply to a long stream.
+        let symbols = symbols(300);
+        let (num, den) = value(&symbols);
+        let cases = [
+            (protocol::Primer::Turn, den.clone(), num.clone()),
+            (protocol::Primer::Ground, -den, num),
+        ];
+        for (primer, num, den) in cases.iter() {
+            let (k, fraction) =
+                Number::split_integer(Number::from_symbols(Some(*primer), symbols.clone()));
+            let (q, r) = num.div_mod_floor(den);
+            assert_eq!(k, q);
+            let zero = BigInt::default();
+            assert_eq!(
+                expanded(fraction),
+                expanded(Number::resume(None, zero.clone(), r, zero, den.clone()))
+            );
         }
+    }
+
+    #[test]
+    fn splits_lazily() {
+        // The integer part is known before the input ends.
+        let symbols = symbols(300);
+        let mut consume = Consume::new(Number::from_symbols(
+            Some(protocol::Primer::Turn),
+            symbols.clone(),
+        ));
+        consume.consume(30);
+        assert!(consume.floor().is_some());
+    }
+}
+
+/**
+ * Resumable transfer of information from a Number to a homographic transformation
+ * with unbounded coefficients.
+ *
+ * At any time the consumed Number equals _(nx * y + n) / (dx * y + d)_,
+ * where _y_ is made of the symbols not yet consumed, or is one half once all were.
+ * Turned back into a Number, it is the strategy that egests the symbols of this value.
+ */
+pub struct Consume {
+    x: Option<Clog>,
+    nx: BigInt,
+    n: BigInt,
+    dx: BigInt,
+    d: BigInt,
+    ingested: usize,
+    reductions: usize,
+}
 
-        fn image_extremes(&self) -> (isize, isize, isize, isize) {
-            let (n0, d0) = self.value_at_zero();
-            let (n1, d1) = self.value_at_one();
-            support::updated_range(n0, d0, n0, d0, n1, d1)
+pub fn new(x: Number) -> (Option<Clog>, isize, isize, isize, isize) {
+    let mut consume = Consume::new(x);
+    let lim = BigInt::from(isize::MAX / 4);
+    let is_state_unsafe = |consume: &Consume| {
+        [&consume.nx, &consume.n, &consume.dx, &consume.d]
+            .iter()
+            .any(|c| **c >= lim || -*c >= lim)
+    };
+    while consume.consume(1) == 1 && !is_state_unsafe(&consume) {}
+    let (nx, n, dx, d) = (
+        consume.nx.to_isize().unwrap(),
+        consume.n.to_isize().unwrap(),
+        consume.dx.to_isize().unwrap(),
+        consume.d.to_isize().unwrap(),
+    );
+    (consume.x, nx, n, dx, d)
+}
+
+/**
+ * The integer part of x, and a Number for the remaining fraction in [0, 1).
+ *
+ * Symbols of x are consumed until the image of the remaining input lies between
+ * consecutive integers, so this does not return for an endless x that is an integer.
+ */
+pub fn split_integer(x: Number) -> (BigInt, Number) {
+    let mut consume = Consume::new(x);
+    loop {
+        if let Some(k) = consume.floor() {
+            consume.nx = &consume.nx - &(&k * &consume.dx);
+            consume.n = &consume.n - &(&k * &consume.d);
+            return (k, consume.into_number());
         }
+        consume.consume(1);
+    }
+}
 
-        fn are_singularities_outside_domain(&self) -> bool {
-            self.is_zero_outside_domain() && self.is_pole_outside_domain()
+impl Consume {
+    /// Prepare to consume x, taking its primer or special value into the coefficients.
+    pub fn new(x: Number) -> Consume {
+        let (primer, x) = match x {
+            Number::Special(s) => {
+                let n = match s {
+                    protocol::Special::NegOne => -1,
+                    protocol::Special::Zero => 0,
+                    protocol::Special::PosOne => 1,
+                };
+                (None, Consume::identity(None, 0, n, 0, 1))
+            }
+            Number::Other(primer, c) => (primer, Consume::identity(Some(c), 1, 0, 0, 1)),
+        };
+        let mut consume = x;
+        match primer {
+            Some(protocol::Primer::Turn) => {
+                swap(&mut consume.nx, &mut consume.n);
+                swap(&mut consume.dx, &mut consume.d);
+            }
+            Some(protocol::Primer::Reflect) => {
+                consume.nx = -&consume.nx;
+                consume.dx = -&consume.dx;
+            }
+            Some(protocol::Primer::Ground) => {
+                consume.nx = -&consume.nx;
+                consume.dx = -&consume.dx;
+                swap(&mut consume.nx, &mut consume.n);
+                swap(&mut consume.dx, &mut consume.d);
+            }
+            None => {}
         }
+        consume
+    }
 
-        fn is_domain_amenable(mx: isize, m: isize) -> bool {
-            let s = m.signum();
-            s != 0 && s == mx.checked_add(m).unwrap().signum()
+    fn identity(x: Option<Clog>, nx: isize, n: isize, dx: isize, d: isize) -> Consume {
+        Consume {
+            x,
+            nx: BigInt::from(nx),
+            n: BigInt::from(n),
+            dx: BigInt::from(dx),
+            d: BigInt::from(d),
+            ingested: 0,
+            reductions: 0,
         }
+    }
 
-        fn is_pole_outside_domain(&self) -> bool {
-            Homographic::is_domain_amenable(self.dx, self.d)
+    /**
+     * Consume up to budget more symbols.
+     *
+     * Returns the number of symbols consumed, which is less than budget only if the Number ended.
+     */
+    pub fn consume(&mut self, budget: usize) -> usize {
+        let mut consumed = 0;
+        while consumed < budget {
+            match self.x.as_mut().and_then(Clog::egest) {
+                Some(protocol::Reduction::Amplify) => self.amplify_x(),
+                Some(protocol::Reduction::Uncover) => self.uncover_x(),
+                None => {
+                    self.x = None;
+                    break;
+                }
+            }
+            consumed += 1;
         }
+        consumed
+    }
+
+    /// Whether every symbol was consumed, so that the coefficients alone give the value at one half.
+    pub fn is_exhausted(&self) -> bool {
+        self.x.is_none()
+    }
+
+    /// Number of symbols consumed so far.
+    pub fn ingested(&self) -> usize {
+        self.ingested
+    }
+
+    /// Coefficients _nx_, _n_, _dx_, _d_.
+    pub fn state(&self) -> (&BigInt, &BigInt, &BigInt, &BigInt) {
+        (&self.nx, &self.n, &self.dx, &self.d)
+    }
+
+    /// The Number made of the symbols not yet consumed, if any, and the coefficients.
+    pub fn into_parts(self) -> (Option<Number>, BigInt, BigInt, BigInt, BigInt) {
+        (
+            self.x.map(|c| Number::Other(None, c)),
+            self.nx,
+            self.n,
+            self.dx,
+            self.d,
+        )
+    }
 
-        fn is_zero_outside_domain(&self) -> bool {
-            Homographic::is_domain_amenable(self.nx, self.n)
+    /// The integer part of the value, if known from the symbols consumed so far.
+    fn floor(&self) -> Option<BigInt> {
+        if self.is_exhausted() {
+            let num = &self.nx + &(&self.n << 1);
+            let den = &self.dx + &(&self.d << 1);
+            return Some(num.div_mod_floor(&den).0);
+        }
+        if !Consume::is_domain_amenable(&self.dx, &self.d)
+            || self.d.is_zero()
+            || (&self.d + &self.dx).is_zero()
+        {
+            // The image is unbounded.
+            return None;
+        }
+        let ((nmin, dmin), (nmax, dmax)) = self.image_extremes()?;
+        let k = nmin.div_mod_floor(&dmin).0;
+        // The image excludes its extremes, which are values at the ends of the domain.
+        let above = (&k + &BigInt::from(1isize), BigInt::from(1isize));
+        if compare(&(nmax, dmax), &above) == Ordering::Greater {
+            None
+        } else {
+            Some(k)
         }
+    }
 
-        fn value_at_one(&self) -> (isize, isize) {
-            (
-                self.n.checked_add(self.nx).unwrap(),
-                self.d.checked_add(self.dx).unwrap(),
-            )
+    fn amplify_x(&mut self) {
+        if !self.nx.is_even() || !self.dx.is_even() {
+            self.n = &self.n << 1;
+            self.d = &self.d << 1;
+        } else {
+            self.nx = &self.nx >> 1;
+            self.dx = &self.dx >> 1;
         }
+        self.count_ingestion();
+    }
 
-        fn value_at_zero(&self) -> (isize, isize) {
-            (self.n, self.d)
+    fn uncover_x(&mut self) {
+        self.nx = &self.nx + &self.n;
+        self.dx = &self.dx + &self.d;
+        swap(&mut self.nx, &mut self.n);
+        swap(&mut self.dx, &mut self.d);
+        self.count_ingestion();
+    }
+
+    /// Count one ingestion, periodically dividing coefficients by their gcd.
+    fn count_ingestion(&mut self) {
+        self.ingested += 1;
+        if self.ingested.is_multiple_of(support::REDUCTION_PERIOD) {
+            self.reduce();
         }
+    }
 
-        fn value_at_one_half(&self) -> (isize, isize) {
-            if self.nx % 2 != 0 || self.dx % 2 != 0 {
-                (
-                    self.nx.checked_add(self.n.checked_mul(2).unwrap()).unwrap(),
-                    self.dx.checked_add(self.d.checked_mul(2).unwrap()).unwrap(),
-                )
-            } else {
-                (
-                    self.n.checked_add(self.nx / 2).unwrap(),
-                    self.d.checked_add(self.dx / 2).unwrap(),
-                )
-            }
+    /**
+     * Divide coefficients by their common power of two.
+     *
+     * Ingestions and egestions only multiply the determinant _nx * d - n * dx_ by -1, 2 or 1/2,
+     * so powers of two are the only common factors they introduce;
+     * other factors of given coefficients are removed once by reduce_gcd.
+     */
+    fn reduce(&mut self) {
+        let shift = [&self.nx, &self.n, &self.dx, &self.d]
+            .iter()
+            .filter_map(|c| c.trailing_zeros())
+            .min()
+            .unwrap_or(0);
+        if shift > 0 {
+            self.nx = &self.nx >> shift;
+            self.n = &self.n >> shift;
+            self.dx = &self.dx >> shift;
+            self.d = &self.d >> shift;
+            self.reductions += 1;
         }
+    }
 
-        fn ingest_amplify(&mut self) {
-            if self.nx % 2 != 0 || self.dx % 2 != 0 {
-                self.n = self.n.checked_mul(2).unwrap();
-                self.d = self.d.checked_mul(2).unwrap();
-            } else {
-                self.nx /= 2;
-                self.dx /= 2;
-            }
+    /**
+     * Prepare to resume from coefficients of a transformation of x, as returned by into_parts.
+     *
+     * The value is _(nx * x + n) / (dx * x + d)_, with x taken as one half when None.
+     */
+    pub fn from_parts(x: Option<Number>, nx: BigInt, n: BigInt, dx: BigInt, d: BigInt) -> Consume {
+        let mut consume = match x {
+            Some(x) => Consume::new(x),
+            None => Consume::identity(None, 1, 0, 0, 1),
+        };
+        let (ix, i, jx, j) = (consume.nx, consume.n, consume.dx, consume.d);
+        consume.nx = &(&nx * &ix) + &(&n * &jx);
+        consume.n = &(&nx * &i) + &(&n * &j);
+        consume.dx = &(&dx * &ix) + &(&d * &jx);
+        consume.d = &(&dx * &i) + &(&d * &j);
+        consume.collapse();
+        consume.reduce_gcd();
+        consume
+    }
+
+    /// Divide coefficients by their greatest common divisor.
+    fn reduce_gcd(&mut self) {
+        let g = [&self.n, &self.dx, &self.d]
+            .iter()
+            .fold(self.nx.clone(), |g, c| gcd(&g, c));
+        if g.is_zero() || g == BigInt::from(1isize) {
+            return;
         }
+        self.nx = self.nx.div_mod_floor(&g).0;
+        self.n = self.n.div_mod_floor(&g).0;
+        self.dx = self.dx.div_mod_floor(&g).0;
+        self.d = self.d.div_mod_floor(&g).0;
+        self.reductions += 1;
+    }
 
-        fn ingest_uncover(&mut self) {
-            self.nx = self.nx.checked_add(self.n).unwrap();
-            self.dx = self.dx.checked_add(self.d).unwrap();
+    /// Drop x if the value does not depend on it, which is when the determinant is zero.
+    fn collapse(&mut self) {
+        if !(&(&self.nx * &self.d) - &(&self.n * &self.dx)).is_zero() {
+            return;
+        }
+        self.x = None;
+        if self.n.is_zero() && self.d.is_zero() {
             swap(&mut self.nx, &mut self.n);
             swap(&mut self.dx, &mut self.d);
+        } else {
+            self.nx = BigInt::default();
+            self.dx = BigInt::default();
         }
+    }
 
-        fn reduction_ingest(&mut self) -> Option<Ratio> {
-            match self.x.egest() {
-                None => {
-                    let (num, den) = self.value_at_one_half();
-                    match ratio::new_i(num, den) {
-                        (None, None, Some(ratio)) => Some(ratio),
-                        _ => panic!("logic error"),
-                    }
-                }
-                Some(protocol::Reduction::Amplify) => {
-                    self.ingest_amplify();
-                    None
-                }
-                Some(protocol::Reduction::Uncover) => {
-                    self.ingest_uncover();
-                    None
-                }
-            }
+    /// A Number that continues emitting the symbols of what is not yet consumed.
+    pub fn into_number(self) -> Number {
+        match self.prime() {
+            (Some(special), _, _, _) => Number::Special(special),
+            (None, primer, Some(ratio), _) => Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(ratio),
+                },
+            ),
+            (None, primer, None, consume) => Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(consume.unwrap()),
+                },
+            ),
         }
+    }
 
-        fn prime(
-            mut self,
-        ) -> (
-            Option<protocol::Special>,
-            Option<protocol::Primer>,
-            Option<Ratio>,
-            Option<Homographic>,
-        ) {
+    fn prime(
+        mut self,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+        Option<Consume>,
+    ) {
+        self.collapse();
+        loop {
             if self.are_singularities_outside_domain() {
                 if let Ok(primer) = self.primer_egest() {
                     return (None, primer, None, Some(self));
                 }
             }
-            match self.prime_ingest() {
-                Some((special, primer, ratio)) => (special, primer, ratio, None),
-                None => self.prime(),
+            let reduction = self.x.as_mut().and_then(Clog::egest);
+            if let Some((special, primer, ratio)) = self.reduction_ingest(reduction) {
+                return (special, primer, ratio, None);
             }
         }
+    }
 
-        fn reduction_egest(&mut self) -> Result<Option<protocol::Reduction>, isize> {
-            let (nmin, dmin, nmax, dmax) = self.image_extremes();
-            // FIXME: remove sanity checks?
-            if support::not_greater_than_zero(nmin, dmin) {
-                panic!("logic error");
-            }
-            if support::not_less_than_one(nmax, dmax) {
-                panic!("logic error");
+    fn primer_egest(&mut self) -> Result<Option<protocol::Primer>, isize> {
+        let (min, max) = self.image_extremes().ok_or(0isize)?;
+        let is = |v: &(BigInt, BigInt), k: isize, ordering: Ordering| {
+            compare(v, &value(k, 1)) == ordering
+        };
+        let is_not = |v: &(BigInt, BigInt), k: isize, ordering: Ordering| {
+            compare(v, &value(k, 1)) != ordering
+        };
+        if compare(&min, &max) == Ordering::Equal
+            && (is(&min, -1, Ordering::Equal)
+                || is(&min, 0, Ordering::Equal)
+                || is(&min, 1, Ordering::Equal))
+        {
+            Err(0)
+        } else if is_not(&max, -1, Ordering::Greater) {
+            // max <= -1
+            Ok(Some(self.ground()))
+        } else if is_not(&min, 1, Ordering::Less) {
+            // min >= 1
+            Ok(Some(self.turn()))
+        } else if is_not(&min, -1, Ordering::Less) && is_not(&max, 0, Ordering::Greater) {
+            // min >= -1 && max <= 0
+            Ok(Some(self.reflect()))
+        } else if is_not(&min, 0, Ordering::Less) && is_not(&max, 1, Ordering::Greater) {
+            // min >= 0 && max <= 1
+            Ok(None)
+        } else {
+            Err(0)
+        }
+    }
+
+    fn ground(&mut self) -> protocol::Primer {
+        self.dx = -&self.dx;
+        self.d = -&self.d;
+        swap(&mut self.nx, &mut self.dx);
+        swap(&mut self.n, &mut self.d);
+        protocol::Primer::Ground
+    }
+
+    fn reflect(&mut self) -> protocol::Primer {
+        self.nx = -&self.nx;
+        self.n = -&self.n;
+        protocol::Primer::Reflect
+    }
+
+    fn turn(&mut self) -> protocol::Primer {
+        swap(&mut self.nx, &mut self.dx);
+        swap(&mut self.n, &mut self.d);
+        protocol::Primer::Turn
+    }
+
+    /// Least and greatest values over the domain, or None if undetermined.
+    fn image_extremes(&self) -> Option<((BigInt, BigInt), (BigInt, BigInt))> {
+        let (n0, d0) = (&self.n, &self.d);
+        let (n1, d1) = (&self.n + &self.nx, &self.d + &self.dx);
+        if (n0.is_zero() && d0.is_zero()) || (n1.is_zero() && d1.is_zero()) {
+            return None;
+        }
+        let (v0, v1) = (normalized(n0, d0), normalized(&n1, &d1));
+        if compare(&v0, &v1) == Ordering::Greater {
+            Some((v1, v0))
+        } else {
+            Some((v0, v1))
+        }
+    }
+
+    fn are_singularities_outside_domain(&self) -> bool {
+        Consume::is_domain_amenable(&self.nx, &self.n)
+            && Consume::is_domain_amenable(&self.dx, &self.d)
+    }
+
+    fn is_domain_amenable(mx: &BigInt, m: &BigInt) -> bool {
+        let s = m.signum();
+        let t = (mx + m).signum();
+        s == 0 || s == t || t == 0
+    }
+
+    /**
+     * Take the value at one half once x ended, as a Ratio if it fits.
+     *
+     * Otherwise the coefficients collapse to that constant value, which egests on its own.
+     */
+    fn end(
+        &mut self,
+    ) -> Option<(
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+    )> {
+        self.x = None;
+        let num = &self.nx + &(&self.n << 1);
+        let den = &self.dx + &(&self.d << 1);
+        let g = gcd(&num, &den);
+        let (num, den) = if g.is_zero() {
+            (num, den)
+        } else {
+            (num.div_mod_floor(&g).0, den.div_mod_floor(&g).0)
+        };
+        if let (Some(num), Some(den)) = (num.to_isize(), den.to_isize()) {
+            return Some(ratio::new_i(num, den));
+        }
+        if self.nx.is_zero() && self.dx.is_zero() {
+            // Already constant, yet undetermined.
+            panic!("logic error");
+        }
+        self.nx = BigInt::default();
+        self.n = num;
+        self.dx = BigInt::default();
+        self.d = den;
+        None
+    }
+
+    fn reduction_ingest(
+        &mut self,
+        reduction: Option<protocol::Reduction>,
+    ) -> Option<(
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+    )> {
+        match reduction {
+            None => self.end(),
+            Some(protocol::Reduction::Amplify) => {
+                self.amplify_x();
+                None
             }
-            if support::less_than_one_half(nmax, dmax) {
-                Ok(Some(self.amplify()))
-            } else if support::greater_than_one_half(nmin, dmin) {
-                Ok(Some(self.uncover()))
-            } else {
-                Err(0)
+            Some(protocol::Reduction::Uncover) => {
+                self.uncover_x();
+                None
             }
         }
+    }
 
-        fn uncover(&mut self) -> protocol::Reduction {
-            self.dx = self.dx.checked_sub(self.nx).unwrap();
-            self.d = self.d.checked_sub(self.n).unwrap();
-            swap(&mut self.nx, &mut self.dx);
-            swap(&mut self.n, &mut self.d);
-            protocol::Reduction::Uncover
+    #[allow(clippy::if_same_then_else)]
+    fn reduction_egest(&mut self) -> Result<Option<protocol::Reduction>, isize> {
+        let (min, max) = self.image_extremes().ok_or(0isize)?;
+        let one_half = value(1, 2);
+        if compare(&min, &value(0, 1)) == Ordering::Less {
+            panic!("logic error");
+        } else if compare(&max, &value(1, 1)) == Ordering::Greater {
+            panic!("logic error");
+        } else if compare(&min, &one_half) == Ordering::Equal
+            && compare(&max, &one_half) == Ordering::Equal
+        {
+            Ok(None)
+        } else if compare(&max, &one_half) != Ordering::Greater {
+            Ok(Some(self.amplify()))
+        } else if compare(&min, &one_half) != Ordering::Less {
+            Ok(Some(self.uncover()))
+        } else {
+            Err(0)
         }
+    }
 
-        fn amplify(&mut self) -> protocol::Reduction {
-            if self.dx % 2 != 0 || self.d % 2 != 0 {
-                self.nx = self.nx.checked_mul(2).unwrap();
-                self.n = self.n.checked_mul(2).unwrap();
-            } else {
-                self.dx /= 2;
-                self.d /= 2;
+    /// Take a single decision, ingesting input symbols from x with ingest if no symbol is determined.
+    fn step_with<F>(&mut self, ingest: F) -> Result<Poll<Step>, Box<dyn Strategy>>
+    where
+        F: FnOnce(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        if self.are_singularities_outside_domain() {
+            if let Ok(reduction) = self.reduction_egest() {
+                return Ok(Poll::Ready(Step::Egest(reduction)));
+            }
+        }
+        let reduction = match self.x.as_mut().map(ingest) {
+            Some(Poll::Ready(reduction)) => reduction,
+            Some(Poll::Pending) => return Ok(Poll::Pending),
+            None => None,
+        };
+        match self.reduction_ingest(reduction) {
+            Some((None, None, Some(ratio))) => Err(Box::new(ratio)),
+            Some(_) => panic!("logic error"),
+            None => Ok(Poll::Ready(Step::Ingest)),
+        }
+    }
+
+    /// Egest a symbol, taking input symbols from x with ingest until one is determined.
+    fn egest_with<F>(
+        &mut self,
+        mut ingest: F,
+    ) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>>
+    where
+        F: FnMut(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        loop {
+            match self.step_with(&mut ingest)? {
+                Poll::Ready(Step::Egest(reduction)) => return Ok(Poll::Ready(reduction)),
+                Poll::Ready(Step::Ingest) => {}
+                Poll::Pending => return Ok(Poll::Pending),
             }
-            protocol::Reduction::Amplify
         }
+    }
+
+    fn uncover(&mut self) -> protocol::Reduction {
+        self.dx = &self.dx - &self.nx;
+        self.d = &self.d - &self.n;
+        swap(&mut self.nx, &mut self.dx);
+        swap(&mut self.n, &mut self.d);
+        protocol::Reduction::Uncover
+    }
+
+    fn amplify(&mut self) -> protocol::Reduction {
+        if !self.dx.is_even() || !self.d.is_even() {
+            self.nx = &self.nx << 1;
+            self.n = &self.n << 1;
+        } else {
+            self.dx = &self.dx >> 1;
+            self.d = &self.d >> 1;
+        }
+        protocol::Reduction::Amplify
+    }
+}
 
-    */
+/// The value _n / d_, as compared by compare.
+fn value(n: isize, d: isize) -> (BigInt, BigInt) {
+    (BigInt::from(n), BigInt::from(d))
+}
+
+/// The value _n / d_ with a nonnegative denominator, infinities taken as _±1 / 0_.
+fn normalized(n: &BigInt, d: &BigInt) -> (BigInt, BigInt) {
+    let (n, d) = if d.signum() < 0 {
+        (-n, -d)
+    } else {
+        (n.clone(), d.clone())
+    };
+    if d.is_zero() {
+        (BigInt::from(n.signum()), d)
+    } else {
+        (n, d)
+    }
+}
+
+/// Compare values with nonnegative denominators.
+fn compare((n0, d0): &(BigInt, BigInt), (n1, d1): &(BigInt, BigInt)) -> Ordering {
+    match (d0.is_zero(), d1.is_zero()) {
+        (true, true) => n0.cmp(n1),
+        (true, false) => n0.signum().cmp(&0),
+        (false, true) => 0.cmp(&n1.signum()),
+        (false, false) => (n0 * d1).cmp(&(n1 * d0)),
+    }
+}
+
+/// Greatest common divisor, nonnegative.
+fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let (mut a, mut b) = (a.clone(), b.clone());
+    while !b.is_zero() {
+        let r = a.div_mod_floor(&b).1;
+        a = b;
+        b = r;
+    }
+    if a.signum() < 0 {
+        -a
+    } else {
+        a
+    }
 }
 
 impl Strategy for Consume {
+    fn coefficients(&self) -> Vec<isize> {
+        support::narrowed(&[&self.nx, &self.n, &self.dx, &self.d])
+    }
+
+    fn reductions(&self) -> usize {
+        self.reductions
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        self.x.iter_mut().collect()
+    }
+
     fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
-        Ok(None)
+        match self.egest_with(|x| Poll::Ready(x.egest()))? {
+            Poll::Ready(reduction) => Ok(reduction),
+            Poll::Pending => panic!("logic error"),
+        }
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        self.egest_with(Clog::poll_egest)
+    }
+
+    fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+        match self.step_with(|x| Poll::Ready(x.egest()))? {
+            Poll::Ready(step) => Ok(step),
+            Poll::Pending => panic!("logic error"),
+        }
+    }
+
+    fn image(&self) -> Vec<isize> {
+        match self.image_extremes() {
+            Some(((nmin, dmin), (nmax, dmax))) => support::narrowed(&[&nmin, &dmin, &nmax, &dmax]),
+            None => Vec::new(),
+        }
     }
 }
@@ -22,12 +22,19 @@ use crate::protocol;
 use crate::strategy::ratio;
 use crate::strategy::ratio::Ratio;
 use crate::strategy::support;
-use crate::strategy::Strategy;
+use crate::strategy::{Step, Strategy};
 use crate::Clog;
 use crate::Number;
+use std::any::Any;
 use std::mem::swap;
+use std::task::Poll;
 
 #[cfg(test)]
+#[allow(
+    clippy::items_after_test_module,
+    clippy::legacy_numeric_constants,
+    clippy::single_match
+)]
 mod tests {
 
     use super::*;
@@ -816,6 +823,66 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn long_prime() {
+        // x / x is one, and amplify ingestions leave its coefficients unchanged.
+        assert_eq(
+            Number::homographic(crate::strategy::tests::amplified(300_000), 1, 0, 1, 0),
+            one(),
+        );
+    }
+
+    #[test]
+    fn long_egest() {
+        // Past its primer, (x + 1) / 3 is undecided until its input ends,
+        // which takes a long run of strategy switches.
+        let (_, x) = crate::strategy::tests::relayed(300_000).unwrap_other();
+        let mut clog = Clog {
+            strategy: Box::new(Homographic {
+                x,
+                nx: 1,
+                n: 1,
+                dx: 0,
+                d: 3,
+                ingested: 0,
+                reductions: 0,
+            }),
+        };
+        let profile = clog.profile();
+        assert_eq!(clog.egest(), None);
+        assert_eq!(profile.stats().transitions, vec!["Ratio"]);
+    }
+
+    #[test]
+    fn reduces_common_factor() {
+        let k = 3isize.pow(29);
+        let (_, mut clog) =
+            Number::homographic(Number::ratio(12345, 67891), k, 2 * k, 3 * k, 5 * k).unwrap_other();
+        let profile = clog.profile();
+        let (_, expected) =
+            Number::homographic(Number::ratio(12345, 67891), 1, 2, 3, 5).unwrap_other();
+        assert!(clog.by_ref().eq(expected));
+        assert!(profile.stats().reductions > 0);
+    }
+
+    #[test]
+    fn fuses_nested() {
+        fn x() -> Number {
+            Number::homographic(Number::ratio(1, 1000), 1, 1, 0, 1)
+        }
+        fn y() -> Number {
+            Number::homographic(x(), 2, 0, 1, 3)
+        }
+        assert!(peek(&x()).is_some());
+        assert!(peek(&y()).is_some());
+        assert!(peek(&unwrap(y())).is_none());
+        assert_eq(y(), Number::ratio(2002, 4001));
+        assert_eq(
+            Number::homographic(Number::neg(x()), 1, 0, 1, 1),
+            Number::ratio(1001, 1),
+        );
+    }
+
     #[test]
     #[ignore] // FIXME: issue #2
     fn rec_incr_does_not_overflow2() {
@@ -835,14 +902,16 @@ pub struct Homographic {
     n: isize,
     dx: isize,
     d: isize,
+    ingested: usize,
+    reductions: usize,
 }
 
 pub fn new(
     x: Number,
-    mut nx: isize,
-    mut n: isize,
-    mut dx: isize,
-    mut d: isize,
+    nx: isize,
+    n: isize,
+    dx: isize,
+    d: isize,
 ) -> (
     Option<protocol::Special>,
     Option<protocol::Primer>,
@@ -862,19 +931,23 @@ pub fn new(
         (special, primer, ratio, None)
     }
 
+    let (x, [mut nx, mut n, mut dx, mut d]) = fuse(x, [nx, n, dx, d]);
+
     if nx == 0 && dx == 0 {
         return as_ratio(n, d);
     }
 
     if let Number::Special(special) = x {
         return match special {
-            protocol::Special::NegOne => {
-                as_ratio(n.checked_sub(nx).unwrap(), d.checked_sub(dx).unwrap())
-            }
+            protocol::Special::NegOne => as_ratio(
+                n.checked_sub(nx).expect(support::OVERFLOW),
+                d.checked_sub(dx).expect(support::OVERFLOW),
+            ),
             protocol::Special::Zero => as_ratio(n, d),
-            protocol::Special::PosOne => {
-                as_ratio(n.checked_add(nx).unwrap(), d.checked_add(dx).unwrap())
-            }
+            protocol::Special::PosOne => as_ratio(
+                n.checked_add(nx).expect(support::OVERFLOW),
+                d.checked_add(dx).expect(support::OVERFLOW),
+            ),
         };
     }
 
@@ -885,12 +958,12 @@ pub fn new(
             swap(&mut dx, &mut d);
         }
         Some(protocol::Primer::Reflect) => {
-            nx = -nx;
-            dx = -dx;
+            nx = nx.checked_neg().expect(support::OVERFLOW);
+            dx = dx.checked_neg().expect(support::OVERFLOW);
         }
         Some(protocol::Primer::Ground) => {
-            nx = -nx;
-            dx = -dx;
+            nx = nx.checked_neg().expect(support::OVERFLOW);
+            dx = dx.checked_neg().expect(support::OVERFLOW);
             swap(&mut nx, &mut n);
             swap(&mut dx, &mut d);
         }
@@ -899,6 +972,77 @@ pub fn new(
     Homographic::new(x_clog, nx, n, dx, d)
 }
 
+/**
+ * A Homographic strategy for a transformation already known to map the domain into itself.
+ *
+ * Unlike new, no input is ingested, as there is no primer to determine.
+ */
+pub fn new_primed(x: Clog, nx: isize, n: isize, dx: isize, d: isize) -> Homographic {
+    let mut homographic = Homographic {
+        x,
+        nx,
+        n,
+        dx,
+        d,
+        ingested: 0,
+        reductions: 0,
+    };
+    homographic.reduce();
+    homographic
+}
+
+/// Fused coefficients beyond this magnitude are left to separate strategies,
+/// which can shed magnitude by egesting.
+pub const FUSION_LIMIT: isize = 1 << 12;
+
+/**
+ * Compose the transformation _m_ with any chain of Homographic strategies driving x.
+ *
+ * Returns the innermost input and the coefficients of a single equivalent transformation,
+ * so that only one strategy needs to run.
+ */
+pub fn fuse(mut x: Number, mut m: [isize; 4]) -> (Number, [isize; 4]) {
+    while let Some(inner) = peek(&x) {
+        let fused = support::compose(m, inner);
+        if !support::are_within(&fused, FUSION_LIMIT) {
+            break;
+        }
+        m = fused;
+        x = unwrap(x);
+    }
+    (x, m)
+}
+
+/**
+ * If the Clog of x is driven by a Homographic strategy,
+ * the coefficients of x as a transformation of the input of that strategy.
+ */
+pub fn peek(x: &Number) -> Option<[isize; 4]> {
+    match x {
+        Number::Other(primer, clog) => {
+            let inner = (&*clog.strategy as &dyn Any).downcast_ref::<Homographic>()?;
+            // The value of x is the primer applied to the value of the inner transformation.
+            let p = match primer {
+                None => [1, 0, 0, 1],
+                Some(protocol::Primer::Turn) => [0, 1, 1, 0],
+                Some(protocol::Primer::Reflect) => [-1, 0, 0, 1],
+                Some(protocol::Primer::Ground) => [0, -1, 1, 0],
+            };
+            Some(support::compose(p, [inner.nx, inner.n, inner.dx, inner.d]))
+        }
+        Number::Special(_) => None,
+    }
+}
+
+/// The input of the Homographic strategy driving the Clog of x, or panic.
+pub fn unwrap(x: Number) -> Number {
+    let (_, clog) = x.unwrap_other();
+    match (clog.strategy as Box<dyn Any>).downcast::<Homographic>() {
+        Ok(inner) => Number::Other(None, inner.x),
+        Err(_) => panic!("Number is not Homographic"),
+    }
+}
+
 impl Homographic {
     fn new(
         x: Clog,
@@ -912,7 +1056,17 @@ impl Homographic {
         Option<Ratio>,
         Option<Homographic>,
     ) {
-        (Homographic { x, nx, n, dx, d }).prime()
+        let mut homographic = Homographic {
+            x,
+            nx,
+            n,
+            dx,
+            d,
+            ingested: 0,
+            reductions: 0,
+        };
+        homographic.reduce();
+        homographic.prime()
     }
 
     fn prime_ingest(
@@ -938,6 +1092,7 @@ impl Homographic {
         }
     }
 
+    #[allow(clippy::if_same_then_else)]
     fn primer_egest(&mut self) -> Result<Option<protocol::Primer>, isize> {
         let (nmin, dmin, nmax, dmax) = self.image_extremes();
         if nmin == 0 && dmin == 0 {
@@ -964,16 +1119,16 @@ impl Homographic {
     }
 
     fn ground(&mut self) -> protocol::Primer {
-        self.dx = -self.dx;
-        self.d = -self.d;
+        self.dx = self.dx.checked_neg().expect(support::OVERFLOW);
+        self.d = self.d.checked_neg().expect(support::OVERFLOW);
         swap(&mut self.nx, &mut self.dx);
         swap(&mut self.n, &mut self.d);
         protocol::Primer::Ground
     }
 
     fn reflect(&mut self) -> protocol::Primer {
-        self.nx = -self.nx;
-        self.n = -self.n;
+        self.nx = self.nx.checked_neg().expect(support::OVERFLOW);
+        self.n = self.n.checked_neg().expect(support::OVERFLOW);
         protocol::Primer::Reflect
     }
 
@@ -1002,8 +1157,8 @@ impl Homographic {
     fn is_domain_amenable(mx: isize, m: isize) -> bool {
         let s = m.signum();
         s == 0
-            || s == mx.checked_add(m).unwrap().signum()
-            || mx.checked_add(m).unwrap().signum() == 0
+            || s == mx.checked_add(m).expect(support::OVERFLOW).signum()
+            || mx.checked_add(m).expect(support::OVERFLOW).signum() == 0
     }
 
     fn is_pole_outside_domain(&self) -> bool {
@@ -1016,8 +1171,8 @@ impl Homographic {
 
     fn value_at_one(&self) -> (isize, isize) {
         (
-            self.n.checked_add(self.nx).unwrap(),
-            self.d.checked_add(self.dx).unwrap(),
+            self.n.checked_add(self.nx).expect(support::OVERFLOW),
+            self.d.checked_add(self.dx).expect(support::OVERFLOW),
         )
     }
 
@@ -1028,36 +1183,58 @@ impl Homographic {
     fn value_at_one_half(&self) -> (isize, isize) {
         if self.nx % 2 != 0 || self.dx % 2 != 0 {
             (
-                self.nx.checked_add(self.n.checked_mul(2).unwrap()).unwrap(),
-                self.dx.checked_add(self.d.checked_mul(2).unwrap()).unwrap(),
+                self.nx
+                    .checked_add(self.n.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
+                self.dx
+                    .checked_add(self.d.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW),
             )
         } else {
             (
-                self.n.checked_add(self.nx / 2).unwrap(),
-                self.d.checked_add(self.dx / 2).unwrap(),
+                self.n.checked_add(self.nx / 2).expect(support::OVERFLOW),
+                self.d.checked_add(self.dx / 2).expect(support::OVERFLOW),
             )
         }
     }
 
     fn ingest_amplify(&mut self) {
         if self.nx % 2 != 0 || self.dx % 2 != 0 {
-            self.n = self.n.checked_mul(2).unwrap();
-            self.d = self.d.checked_mul(2).unwrap();
+            self.n = self.n.checked_mul(2).expect(support::OVERFLOW);
+            self.d = self.d.checked_mul(2).expect(support::OVERFLOW);
         } else {
             self.nx /= 2;
             self.dx /= 2;
         }
+        self.count_ingestion();
     }
 
     fn ingest_uncover(&mut self) {
-        self.nx = self.nx.checked_add(self.n).unwrap();
-        self.dx = self.dx.checked_add(self.d).unwrap();
+        self.nx = self.nx.checked_add(self.n).expect(support::OVERFLOW);
+        self.dx = self.dx.checked_add(self.d).expect(support::OVERFLOW);
         swap(&mut self.nx, &mut self.n);
         swap(&mut self.dx, &mut self.d);
+        self.count_ingestion();
     }
 
-    fn reduction_ingest(&mut self) -> Option<Ratio> {
-        match self.x.egest() {
+    /// Count one ingestion, periodically dividing coefficients by their gcd.
+    fn count_ingestion(&mut self) {
+        self.ingested += 1;
+        if self.ingested.is_multiple_of(support::REDUCTION_PERIOD) {
+            self.reduce();
+        }
+    }
+
+    fn reduce(&mut self) {
+        let mut m = [self.nx, self.n, self.dx, self.d];
+        if support::reduce(&mut m) {
+            self.reductions += 1;
+        }
+        [self.nx, self.n, self.dx, self.d] = m;
+    }
+
+    fn reduction_ingest(&mut self, reduction: Option<protocol::Reduction>) -> Option<Ratio> {
+        match reduction {
             None => {
                 let (num, den) = self.value_at_one_half();
                 match ratio::new_i(num, den) {
@@ -1084,20 +1261,19 @@ impl Homographic {
         Option<Ratio>,
         Option<Homographic>,
     ) {
-        // FIXME: debug
-        println!("{} {} {} {}", self.nx, self.n, self.dx, self.d);
-        if self.are_singularities_outside_domain() {
-            if let Ok(primer) = self.primer_egest() {
-                return (None, primer, None, Some(self));
+        loop {
+            if self.are_singularities_outside_domain() {
+                if let Ok(primer) = self.primer_egest() {
+                    return (None, primer, None, Some(self));
+                }
+            }
+            if let Some((special, primer, ratio)) = self.prime_ingest() {
+                return (special, primer, ratio, None);
             }
-        }
-        match self.prime_ingest() {
-            Some((special, primer, ratio)) => (special, primer, ratio, None),
-            // FIXME: recursion
-            None => self.prime(),
         }
     }
 
+    #[allow(clippy::if_same_then_else)]
     fn reduction_egest(&mut self) -> Result<Option<protocol::Reduction>, isize> {
         let (nmin, dmin, nmax, dmax) = self.image_extremes();
         if support::less_than_zero(nmin, dmin) {
@@ -1115,9 +1291,46 @@ impl Homographic {
         }
     }
 
+    /// Take a single decision, ingesting input symbols from x with ingest if no symbol is determined.
+    fn step_with<F>(&mut self, ingest: F) -> Result<Poll<Step>, Box<dyn Strategy>>
+    where
+        F: FnOnce(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        if self.are_singularities_outside_domain() {
+            if let Ok(reduction) = self.reduction_egest() {
+                return Ok(Poll::Ready(Step::Egest(reduction)));
+            }
+        }
+        let reduction = match ingest(&mut self.x) {
+            Poll::Ready(reduction) => reduction,
+            Poll::Pending => return Ok(Poll::Pending),
+        };
+        match self.reduction_ingest(reduction) {
+            Some(ratio) => Err(Box::new(ratio)),
+            None => Ok(Poll::Ready(Step::Ingest)),
+        }
+    }
+
+    /// Egest a symbol, taking input symbols from x with ingest until one is determined.
+    fn egest_with<F>(
+        &mut self,
+        mut ingest: F,
+    ) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>>
+    where
+        F: FnMut(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        loop {
+            match self.step_with(&mut ingest)? {
+                Poll::Ready(Step::Egest(reduction)) => return Ok(Poll::Ready(reduction)),
+                Poll::Ready(Step::Ingest) => {}
+                Poll::Pending => return Ok(Poll::Pending),
+            }
+        }
+    }
+
     fn uncover(&mut self) -> protocol::Reduction {
-        self.dx = self.dx.checked_sub(self.nx).unwrap();
-        self.d = self.d.checked_sub(self.n).unwrap();
+        self.dx = self.dx.checked_sub(self.nx).expect(support::OVERFLOW);
+        self.d = self.d.checked_sub(self.n).expect(support::OVERFLOW);
         swap(&mut self.nx, &mut self.dx);
         swap(&mut self.n, &mut self.d);
         protocol::Reduction::Uncover
@@ -1125,8 +1338,8 @@ impl Homographic {
 
     fn amplify(&mut self) -> protocol::Reduction {
         if self.dx % 2 != 0 || self.d % 2 != 0 {
-            self.nx = self.nx.checked_mul(2).unwrap();
-            self.n = self.n.checked_mul(2).unwrap();
+            self.nx = self.nx.checked_mul(2).expect(support::OVERFLOW);
+            self.n = self.n.checked_mul(2).expect(support::OVERFLOW);
         } else {
             self.dx /= 2;
             self.d /= 2;
@@ -1136,18 +1349,38 @@ impl Homographic {
 }
 
 impl Strategy for Homographic {
+    fn coefficients(&self) -> Vec<isize> {
+        vec![self.nx, self.n, self.dx, self.d]
+    }
+
+    fn reductions(&self) -> usize {
+        self.reductions
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        vec![&mut self.x]
+    }
+
     fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
-        // FIXME: debug
-        println!("{} {} {} {}", self.nx, self.n, self.dx, self.d);
-        if self.are_singularities_outside_domain() {
-            if let Ok(reduction) = self.reduction_egest() {
-                return Ok(reduction);
-            }
+        match self.egest_with(|x| Poll::Ready(x.egest()))? {
+            Poll::Ready(reduction) => Ok(reduction),
+            Poll::Pending => panic!("logic error"),
         }
-        match self.reduction_ingest() {
-            Some(ratio) => Err(Box::new(ratio)),
-            // FIXME: recursion
-            None => self.egest(),
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        self.egest_with(Clog::poll_egest)
+    }
+
+    fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+        match self.step_with(|x| Poll::Ready(x.egest()))? {
+            Poll::Ready(step) => Ok(step),
+            Poll::Pending => panic!("logic error"),
         }
     }
+
+    fn image(&self) -> Vec<isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        vec![nmin, dmin, nmax, dmax]
+    }
 }
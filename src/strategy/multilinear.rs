@@ -0,0 +1,546 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::ratio;
+use crate::strategy::support;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::mem::swap;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn inputs() -> Vec<(isize, isize)> {
+        vec![
+            (-2, 1),
+            (-1, 1),
+            (-2, 3),
+            (-1, 2),
+            (-1, 4),
+            (0, 1),
+            (1, 4),
+            (1, 2),
+            (2, 3),
+            (1, 1),
+            (2, 1),
+        ]
+    }
+
+    fn multilinear(x: Vec<Number>, num: &[isize], den: &[isize]) -> Number {
+        match new(x, num.to_vec(), den.to_vec()) {
+            (Some(special), _, _) => Number::Special(special),
+            (None, primer, strategy) => Number::Other(
+                primer,
+                Clog {
+                    strategy: strategy.unwrap(),
+                },
+            ),
+        }
+    }
+
+    fn fix(n: isize, d: isize) -> (isize, isize) {
+        if d < 0 {
+            (
+                n.checked_neg().expect(support::OVERFLOW),
+                d.checked_neg().expect(support::OVERFLOW),
+            )
+        } else {
+            (n, d)
+        }
+    }
+
+    #[test]
+    fn no_inputs() {
+        assert_eq!(
+            Number::compare(multilinear(vec![], &[3], &[-6]), Number::ratio(-1, 2)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn forbids_pole() {
+        multilinear(vec![Number::ratio(1, 2)], &[1, 0], &[-1, 2]);
+    }
+
+    #[test]
+    fn matches_homographic() {
+        for (xn, xd) in inputs() {
+            // (2x + 3) / (x + 5)
+            let (rn, rd) = fix(2 * xn + 3 * xd, xn + 5 * xd);
+            assert_eq!(
+                Number::compare(
+                    multilinear(vec![Number::ratio(xn, xd)], &[3, 2], &[5, 1]),
+                    Number::ratio(rn, rd)
+                ),
+                Ordering::Equal
+            );
+        }
+    }
+
+    #[test]
+    fn matches_combine() {
+        for (xn, xd) in inputs() {
+            for (yn, yd) in inputs() {
+                // (x - y) / (x * y + 3)
+                let (rn, rd) = fix(xn * yd - yn * xd, xn * yn + 3 * xd * yd);
+                assert_eq!(
+                    Number::compare(
+                        multilinear(
+                            vec![Number::ratio(xn, xd), Number::ratio(yn, yd)],
+                            &[0, 1, -1, 0],
+                            &[3, 0, 0, 1]
+                        ),
+                        Number::ratio(rn, rd)
+                    ),
+                    Ordering::Equal
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn three_inputs() {
+        for (xn, xd) in inputs() {
+            for (yn, yd) in inputs() {
+                for (zn, zd) in inputs() {
+                    // (x * y * z + 1) / (x + y + z)
+                    let (rn, rd) = fix(
+                        xn * yn * zn + xd * yd * zd,
+                        xn * yd * zd + yn * xd * zd + zn * xd * yd,
+                    );
+                    if rd == 0 {
+                        continue;
+                    }
+                    assert_eq!(
+                        Number::compare(
+                            multilinear(
+                                vec![
+                                    Number::ratio(xn, xd),
+                                    Number::ratio(yn, yd),
+                                    Number::ratio(zn, zd)
+                                ],
+                                &[1, 0, 0, 0, 0, 0, 0, 1],
+                                &[0, 1, 1, 0, 1, 0, 0, 0]
+                            ),
+                            Number::ratio(rn, rd)
+                        ),
+                        Ordering::Equal,
+                        "({} {}) ({} {}) ({} {})",
+                        xn,
+                        xd,
+                        yn,
+                        yd,
+                        zn,
+                        zd
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn four_inputs() {
+        // x * y + z * w
+        let x = vec![
+            Number::ratio(2, 3),
+            Number::ratio(-1, 4),
+            Number::ratio(3, 2),
+            Number::ratio(5, 7),
+        ];
+        let mut num = vec![0; 16];
+        num[0b0011] = 1;
+        num[0b1100] = 1;
+        let mut den = vec![0; 16];
+        den[0] = 1;
+        assert_eq!(
+            Number::compare(multilinear(x, &num, &den), Number::ratio(19, 21)),
+            Ordering::Equal
+        );
+    }
+}
+
+/**
+ * The multilinear fraction _N(x) / D(x)_ of _k_ inputs.
+ *
+ * The coefficient at index _m_ of `num` (resp. `den`) multiplies the product of
+ * every input _x[i]_ for which bit _i_ of _m_ is set.
+ * This generalizes Combine, whose eight coefficients are the case _k = 2_.
+ *
+ * Ingestion and egestion follow the same rules as in Combine, applied bitwise:
+ * ingesting from _x[i]_ touches the coefficient pairs that differ only in bit _i_;
+ * an exhausted input is replaced by one half and its dimension removed.
+ * When no inputs remain the strategy becomes a Ratio.
+ */
+pub struct Multilinear {
+    x: Vec<Clog>,
+    num: Vec<isize>,
+    den: Vec<isize>,
+}
+
+pub fn new(
+    x: Vec<Number>,
+    num: Vec<isize>,
+    den: Vec<isize>,
+) -> (
+    Option<protocol::Special>,
+    Option<protocol::Primer>,
+    Option<Box<dyn Strategy>>,
+) {
+    assert_eq!(num.len(), 1 << x.len(), "coefficient count mismatch");
+    assert_eq!(den.len(), 1 << x.len(), "coefficient count mismatch");
+    let k = x.len();
+    let mut multilinear = Multilinear {
+        x: Vec::with_capacity(k),
+        num,
+        den,
+    };
+    // Visit inputs from the highest dimension down,
+    // so that removing a dimension does not disturb the ones yet to be visited.
+    for (i, input) in x.into_iter().enumerate().rev() {
+        match input {
+            Number::Special(protocol::Special::NegOne) => {
+                multilinear.substitute(i, -1);
+            }
+            Number::Special(protocol::Special::Zero) => {
+                multilinear.substitute(i, 0);
+            }
+            Number::Special(protocol::Special::PosOne) => {
+                multilinear.substitute(i, 1);
+            }
+            Number::Other(primer, clog) => {
+                match primer {
+                    Some(protocol::Primer::Turn) => {
+                        multilinear.turn_input(i);
+                    }
+                    Some(protocol::Primer::Reflect) => {
+                        multilinear.reflect_input(i);
+                    }
+                    Some(protocol::Primer::Ground) => {
+                        multilinear.turn_input(i);
+                        multilinear.reflect_input(i);
+                    }
+                    None => {}
+                }
+                multilinear.x.push(clog);
+            }
+        }
+    }
+    multilinear.x.reverse();
+    multilinear.prime()
+}
+
+impl Multilinear {
+    fn bit(i: usize) -> usize {
+        1 << i
+    }
+
+    /// Masks with bit _i_ cleared, among the coefficients of the current inputs.
+    fn masks_without(&self, i: usize) -> impl Iterator<Item = usize> {
+        let bit = Multilinear::bit(i);
+        (0..self.num.len()).filter(move |m| m & bit == 0)
+    }
+
+    /// Replace input _i_ by the integer value _v_ and remove its dimension.
+    fn substitute(&mut self, i: usize, v: isize) {
+        self.collapse(i, |lo, hi| {
+            lo.checked_add(v.checked_mul(hi).expect(support::OVERFLOW))
+                .expect(support::OVERFLOW)
+        });
+    }
+
+    /// Remove dimension _i_, combining each pair of coefficients that differ only in bit _i_.
+    fn collapse<F: Fn(isize, isize) -> isize>(&mut self, i: usize, f: F) {
+        let low = Multilinear::bit(i) - 1;
+        let size = self.num.len() / 2;
+        let mut num = Vec::with_capacity(size);
+        let mut den = Vec::with_capacity(size);
+        for m in 0..size {
+            let lo = ((m & !low) << 1) | (m & low);
+            let hi = lo | Multilinear::bit(i);
+            num.push(f(self.num[lo], self.num[hi]));
+            den.push(f(self.den[lo], self.den[hi]));
+        }
+        self.num = num;
+        self.den = den;
+    }
+
+    fn are_even_with(&self, i: usize) -> bool {
+        let bit = Multilinear::bit(i);
+        self.masks_without(i)
+            .all(|m| support::is_even(self.num[m | bit]) && support::is_even(self.den[m | bit]))
+    }
+
+    /// Replace the exhausted input _i_ by one half.
+    fn end_input(&mut self, i: usize) {
+        if self.are_even_with(i) {
+            self.collapse(i, |lo, hi| lo.checked_add(hi / 2).expect(support::OVERFLOW));
+        } else {
+            self.collapse(i, |lo, hi| {
+                hi.checked_add(lo.checked_mul(2).expect(support::OVERFLOW))
+                    .expect(support::OVERFLOW)
+            });
+        }
+        self.x.remove(i);
+    }
+
+    fn amplify_input(&mut self, i: usize) {
+        let bit = Multilinear::bit(i);
+        let masks: Vec<usize> = self.masks_without(i).collect();
+        if self.are_even_with(i) {
+            for m in masks {
+                self.num[m | bit] /= 2;
+                self.den[m | bit] /= 2;
+            }
+        } else {
+            for m in masks {
+                self.num[m] = self.num[m].checked_mul(2).expect(support::OVERFLOW);
+                self.den[m] = self.den[m].checked_mul(2).expect(support::OVERFLOW);
+            }
+        }
+    }
+
+    fn uncover_input(&mut self, i: usize) {
+        self.turn_input(i);
+        self.shift_input(i);
+    }
+
+    fn turn_input(&mut self, i: usize) {
+        let bit = Multilinear::bit(i);
+        let masks: Vec<usize> = self.masks_without(i).collect();
+        for m in masks {
+            self.num.swap(m, m | bit);
+            self.den.swap(m, m | bit);
+        }
+    }
+
+    fn reflect_input(&mut self, i: usize) {
+        let bit = Multilinear::bit(i);
+        let masks: Vec<usize> = self.masks_without(i).collect();
+        for m in masks {
+            self.num[m | bit] = self.num[m | bit].checked_neg().expect(support::OVERFLOW);
+            self.den[m | bit] = self.den[m | bit].checked_neg().expect(support::OVERFLOW);
+        }
+    }
+
+    fn shift_input(&mut self, i: usize) {
+        let bit = Multilinear::bit(i);
+        let masks: Vec<usize> = self.masks_without(i).collect();
+        for m in masks {
+            self.num[m] = self.num[m]
+                .checked_add(self.num[m | bit])
+                .expect(support::OVERFLOW);
+            self.den[m] = self.den[m]
+                .checked_add(self.den[m | bit])
+                .expect(support::OVERFLOW);
+        }
+    }
+
+    /// Values at the corners of the domain; the corner at index _s_ has _x[i] = 1_ for every bit _i_ of _s_.
+    fn corners(coefficients: &[isize]) -> Vec<isize> {
+        let mut corners = coefficients.to_vec();
+        let mut bit = 1;
+        while bit < corners.len() {
+            for s in 0..corners.len() {
+                if s & bit != 0 {
+                    corners[s] = corners[s]
+                        .checked_add(corners[s ^ bit])
+                        .expect(support::OVERFLOW);
+                }
+            }
+            bit <<= 1;
+        }
+        corners
+    }
+
+    fn image_extremes(&self) -> (isize, isize, isize, isize) {
+        let num = Multilinear::corners(&self.num);
+        let den = Multilinear::corners(&self.den);
+        let (mut nmin, mut dmin, mut nmax, mut dmax) = (num[0], den[0], num[0], den[0]);
+        for s in 1..num.len() {
+            let range = support::updated_range(nmin, dmin, nmax, dmax, num[s], den[s]);
+            nmin = range.0;
+            dmin = range.1;
+            nmax = range.2;
+            dmax = range.3;
+        }
+        (nmin, dmin, nmax, dmax)
+    }
+
+    fn is_domain_amenable(coefficients: &[isize]) -> bool {
+        let corners = Multilinear::corners(coefficients);
+        let s = corners[0].signum();
+        s != 0 && corners.iter().all(|c| c.signum() == s)
+    }
+
+    fn are_singularities_outside_domain(&self) -> bool {
+        Multilinear::is_domain_amenable(&self.num) && Multilinear::is_domain_amenable(&self.den)
+    }
+
+    fn primer_egest(&mut self) -> Result<Option<protocol::Primer>, isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        if support::less_than_minus_one(nmax, dmax) {
+            Ok(Some(self.ground()))
+        } else if support::greater_than_minus_one(nmin, dmin) && support::less_than_zero(nmax, dmax)
+        {
+            Ok(Some(self.reflect()))
+        } else if support::greater_than_zero(nmin, dmin) && support::less_than_one(nmax, dmax) {
+            Ok(None)
+        } else if support::greater_than_one(nmin, dmin) {
+            Ok(Some(self.turn()))
+        } else {
+            Err(0)
+        }
+    }
+
+    fn ground(&mut self) -> protocol::Primer {
+        self.turn();
+        self.reflect();
+        protocol::Primer::Ground
+    }
+
+    fn reflect(&mut self) -> protocol::Primer {
+        for c in self.num.iter_mut() {
+            *c = c.checked_neg().expect(support::OVERFLOW);
+        }
+        protocol::Primer::Reflect
+    }
+
+    fn turn(&mut self) -> protocol::Primer {
+        swap(&mut self.num, &mut self.den);
+        protocol::Primer::Turn
+    }
+
+    fn prime(
+        mut self,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Box<dyn Strategy>>,
+    ) {
+        loop {
+            if self.x.is_empty() {
+                let (special, primer, ratio) = ratio::new_i(self.num[0], self.den[0]);
+                return (
+                    special,
+                    primer,
+                    ratio.map(|ratio| Box::new(ratio) as Box<dyn Strategy>),
+                );
+            }
+            if self.are_singularities_outside_domain() {
+                if let Ok(primer) = self.primer_egest() {
+                    return (None, primer, Some(Box::new(self)));
+                }
+            }
+            self.ingest();
+        }
+    }
+
+    /// Ingest one symbol from every input.
+    fn ingest(&mut self) {
+        for i in (0..self.x.len()).rev() {
+            match self.x[i].egest() {
+                None => {
+                    self.end_input(i);
+                }
+                Some(protocol::Reduction::Amplify) => {
+                    self.amplify_input(i);
+                }
+                Some(protocol::Reduction::Uncover) => {
+                    self.uncover_input(i);
+                }
+            }
+        }
+    }
+
+    fn reduction_egest(&mut self) -> Result<Option<protocol::Reduction>, isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        // FIXME: remove sanity checks?
+        if support::not_greater_than_zero(nmin, dmin) {
+            panic!("logic error");
+        }
+        if support::not_less_than_one(nmax, dmax) {
+            panic!("logic error");
+        }
+        if support::less_than_one_half(nmax, dmax) {
+            Ok(Some(self.amplify()))
+        } else if support::greater_than_one_half(nmin, dmin) {
+            Ok(Some(self.uncover()))
+        } else {
+            Err(0)
+        }
+    }
+
+    fn amplify(&mut self) -> protocol::Reduction {
+        if self.den.iter().all(|c| support::is_even(*c)) {
+            for c in self.den.iter_mut() {
+                *c /= 2;
+            }
+        } else {
+            for c in self.num.iter_mut() {
+                *c = c.checked_mul(2).expect(support::OVERFLOW);
+            }
+        }
+        protocol::Reduction::Amplify
+    }
+
+    fn uncover(&mut self) -> protocol::Reduction {
+        self.turn();
+        for m in 0..self.num.len() {
+            self.num[m] = self.num[m]
+                .checked_sub(self.den[m])
+                .expect(support::OVERFLOW);
+        }
+        protocol::Reduction::Uncover
+    }
+}
+
+impl Strategy for Multilinear {
+    fn coefficients(&self) -> Vec<isize> {
+        self.num.iter().chain(self.den.iter()).cloned().collect()
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        self.x.iter_mut().collect()
+    }
+
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        loop {
+            if self.x.is_empty() {
+                return match ratio::new_i(self.num[0], self.den[0]) {
+                    (None, None, Some(ratio)) => Err(Box::new(ratio)),
+                    _ => panic!("logic error"),
+                };
+            }
+            if self.are_singularities_outside_domain() {
+                if let Ok(reduction) = self.reduction_egest() {
+                    return Ok(reduction);
+                }
+            }
+            self.ingest();
+        }
+    }
+}
@@ -0,0 +1,501 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::ratio;
+use crate::strategy::ratio::Ratio;
+use crate::strategy::support;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::mem::swap;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn inputs() -> Vec<(isize, isize)> {
+        vec![
+            (-2, 1),
+            (-1, 1),
+            (-2, 3),
+            (-1, 2),
+            (-1, 4),
+            (0, 1),
+            (1, 4),
+            (1, 2),
+            (2, 3),
+            (1, 1),
+            (2, 1),
+        ]
+    }
+
+    fn rational_fn(x: Number, num: &[isize], den: &[isize]) -> Number {
+        let (special, primer, ratio, polynomial) = new(x, num, den);
+        if let Some(special) = special {
+            Number::Special(special)
+        } else if let Some(ratio) = ratio {
+            Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(ratio),
+                },
+            )
+        } else {
+            Number::Other(
+                primer,
+                Clog {
+                    strategy: Box::new(polynomial.unwrap()),
+                },
+            )
+        }
+    }
+
+    /// Evaluate a polynomial at _xn / xd_, as a fraction with denominator _xd^degree_.
+    fn evaluate(coefficients: &[isize], degree: usize, xn: isize, xd: isize) -> isize {
+        coefficients
+            .iter()
+            .enumerate()
+            .map(|(j, c)| c * xn.pow(j as u32) * xd.pow((degree - j) as u32))
+            .sum()
+    }
+
+    fn t(num: &[isize], den: &[isize]) {
+        let degree = num.len().max(den.len()) - 1;
+        for (xn, xd) in inputs() {
+            let rn = evaluate(num, degree, xn, xd);
+            let rd = evaluate(den, degree, xn, xd);
+            if rd == 0 {
+                continue;
+            }
+            assert_eq!(
+                Number::compare(
+                    rational_fn(Number::ratio(xn, xd), num, den),
+                    Number::ratio(rn, rd)
+                ),
+                Ordering::Equal,
+                "{:?} / {:?} at ({} {}) is not ({} {})",
+                num,
+                den,
+                xn,
+                xd,
+                rn,
+                rd
+            );
+        }
+    }
+
+    #[test]
+    fn constant() {
+        t(&[3], &[-4]);
+        t(&[0], &[1]);
+    }
+
+    #[test]
+    fn homographic() {
+        t(&[3, 2], &[5, 1]);
+        t(&[1, 0], &[0, 1]);
+        t(&[0, -1], &[1, 1]);
+    }
+
+    #[test]
+    fn polynomials() {
+        t(&[0, 0, 1], &[1]);
+        t(&[1, -3, 0, 2], &[1]);
+        t(&[-1, 1, 1, 1, 1], &[1]);
+        t(&[2, 0, 0, 0, 0, -1], &[3]);
+    }
+
+    #[test]
+    fn rational_functions() {
+        t(&[1, 0, 1], &[1, -1, 1]);
+        t(&[0, 1], &[4, 0, 1]);
+        t(&[1, 2, 3], &[3, 2, 1]);
+        t(&[-5, 0, 0, 1], &[2, 0, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn forbids_pole() {
+        rational_fn(Number::ratio(1, 2), &[1], &[-1, 0, 4]);
+    }
+
+    #[test]
+    fn irrational_input() {
+        // x^2 + x at the square root of two is 3.4142...
+        let x = Number::powq(Number::ratio(2, 1), 1, 2);
+        let (x1, x2) = Number::fork(x);
+        assert_eq!(
+            Number::compare(rational_fn(x1, &[0, 1, 1], &[1]), Number::ratio(341, 100)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Number::compare(rational_fn(x2, &[0, 1, 1], &[1]), Number::ratio(342, 100)),
+            Ordering::Less
+        );
+    }
+}
+
+/**
+ * The rational function _P(x) / Q(x)_ of a single input.
+ *
+ * With _x_ mapped to the remaining input _s_ by the symbols already ingested,
+ * the numerator and denominator are kept in the scaled Bernstein basis
+ * _s^i * (1 - s)^(n - i)_ of degree _n_ (common factors dropped).
+ * Whenever the coefficients of each polynomial share the same strict sign,
+ * the image of the domain lies between the least and greatest ratios of corresponding coefficients,
+ * and those bounds tighten as more input is ingested.
+ *
+ * Note that a zero or pole at an irrational input cannot be decided in finite time.
+ */
+pub struct Polynomial {
+    x: Clog,
+    num: Vec<isize>,
+    den: Vec<isize>,
+}
+
+pub fn new(
+    x: Number,
+    num: &[isize],
+    den: &[isize],
+) -> (
+    Option<protocol::Special>,
+    Option<protocol::Primer>,
+    Option<Ratio>,
+    Option<Polynomial>,
+) {
+    let degree = num.len().max(den.len()).max(1) - 1;
+    let mut num = num.to_vec();
+    let mut den = den.to_vec();
+    num.resize(degree + 1, 0);
+    den.resize(degree + 1, 0);
+    if den.iter().all(|c| *c == 0) {
+        panic!("division by zero");
+    }
+
+    fn as_ratio(
+        n: isize,
+        d: isize,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+        Option<Polynomial>,
+    ) {
+        let (special, primer, ratio) = ratio::new_i(n, d);
+        (special, primer, ratio, None)
+    }
+
+    fn evaluate(coefficients: &[isize], v: isize) -> isize {
+        coefficients.iter().rev().fold(0, |acc: isize, c| {
+            acc.checked_mul(v)
+                .expect(support::OVERFLOW)
+                .checked_add(*c)
+                .expect(support::OVERFLOW)
+        })
+    }
+
+    if degree == 0 {
+        return as_ratio(num[0], den[0]);
+    }
+
+    let (x_primer, x_clog) = match x {
+        Number::Special(special) => {
+            let v = match special {
+                protocol::Special::NegOne => -1,
+                protocol::Special::Zero => 0,
+                protocol::Special::PosOne => 1,
+            };
+            return as_ratio(evaluate(&num, v), evaluate(&den, v));
+        }
+        Number::Other(primer, clog) => (primer, clog),
+    };
+
+    fn alternate(coefficients: &mut [isize]) {
+        for c in coefficients.iter_mut().skip(1).step_by(2) {
+            *c = c.checked_neg().expect(support::OVERFLOW);
+        }
+    }
+
+    match x_primer {
+        Some(protocol::Primer::Turn) => {
+            num.reverse();
+            den.reverse();
+        }
+        Some(protocol::Primer::Reflect) => {
+            alternate(&mut num);
+            alternate(&mut den);
+        }
+        Some(protocol::Primer::Ground) => {
+            alternate(&mut num);
+            alternate(&mut den);
+            num.reverse();
+            den.reverse();
+        }
+        None => {}
+    }
+    Polynomial::new(x_clog, bernstein(&num), bernstein(&den))
+}
+
+/**
+ * Convert power basis coefficients to the scaled Bernstein basis of the same degree:
+ * _b[i] = sum of a[k] * C(n - k, i - k) for k <= i_.
+ */
+fn bernstein(a: &[isize]) -> Vec<isize> {
+    let n = a.len() - 1;
+    let mut b: Vec<isize> = vec![0; n + 1];
+    for (k, ak) in a.iter().enumerate() {
+        // C(n - k, i - k) for i = k..=n
+        let mut binomial: isize = 1;
+        for (j, bi) in b.iter_mut().skip(k).enumerate() {
+            *bi = bi
+                .checked_add(ak.checked_mul(binomial).expect(support::OVERFLOW))
+                .expect(support::OVERFLOW);
+            binomial = binomial
+                .checked_mul((n - k - j) as isize)
+                .expect(support::OVERFLOW)
+                / (j + 1) as isize;
+        }
+    }
+    b
+}
+
+impl Polynomial {
+    fn new(
+        x: Clog,
+        num: Vec<isize>,
+        den: Vec<isize>,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+        Option<Polynomial>,
+    ) {
+        (Polynomial { x, num, den }).prime()
+    }
+
+    fn prime(
+        mut self,
+    ) -> (
+        Option<protocol::Special>,
+        Option<protocol::Primer>,
+        Option<Ratio>,
+        Option<Polynomial>,
+    ) {
+        loop {
+            if self.are_singularities_outside_domain() {
+                if let Ok(primer) = self.primer_egest() {
+                    return (None, primer, None, Some(self));
+                }
+            }
+            if let Some((num, den)) = self.ingest() {
+                let (special, primer, ratio) = ratio::new_i(num, den);
+                return (special, primer, ratio, None);
+            }
+        }
+    }
+
+    fn is_domain_amenable(coefficients: &[isize]) -> bool {
+        let s = coefficients[0].signum();
+        s != 0 && coefficients.iter().all(|c| c.signum() == s)
+    }
+
+    fn are_singularities_outside_domain(&self) -> bool {
+        Polynomial::is_domain_amenable(&self.num) && Polynomial::is_domain_amenable(&self.den)
+    }
+
+    fn image_extremes(&self) -> (isize, isize, isize, isize) {
+        let (mut nmin, mut dmin, mut nmax, mut dmax) =
+            (self.num[0], self.den[0], self.num[0], self.den[0]);
+        for i in 1..self.num.len() {
+            let range = support::updated_range(nmin, dmin, nmax, dmax, self.num[i], self.den[i]);
+            nmin = range.0;
+            dmin = range.1;
+            nmax = range.2;
+            dmax = range.3;
+        }
+        (nmin, dmin, nmax, dmax)
+    }
+
+    fn primer_egest(&mut self) -> Result<Option<protocol::Primer>, isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        if support::less_than_minus_one(nmax, dmax) {
+            Ok(Some(self.ground()))
+        } else if support::greater_than_minus_one(nmin, dmin) && support::less_than_zero(nmax, dmax)
+        {
+            Ok(Some(self.reflect()))
+        } else if support::greater_than_zero(nmin, dmin) && support::less_than_one(nmax, dmax) {
+            Ok(None)
+        } else if support::greater_than_one(nmin, dmin) {
+            Ok(Some(self.turn()))
+        } else {
+            Err(0)
+        }
+    }
+
+    fn ground(&mut self) -> protocol::Primer {
+        self.turn();
+        self.reflect();
+        protocol::Primer::Ground
+    }
+
+    fn reflect(&mut self) -> protocol::Primer {
+        for c in self.num.iter_mut() {
+            *c = c.checked_neg().expect(support::OVERFLOW);
+        }
+        protocol::Primer::Reflect
+    }
+
+    fn turn(&mut self) -> protocol::Primer {
+        swap(&mut self.num, &mut self.den);
+        protocol::Primer::Turn
+    }
+
+    /// The value at the end of the input, when the remaining input is one half.
+    fn value_at_one_half(&self) -> (isize, isize) {
+        fn total(coefficients: &[isize]) -> isize {
+            coefficients.iter().fold(0, |acc: isize, c| {
+                acc.checked_add(*c).expect(support::OVERFLOW)
+            })
+        }
+        (total(&self.num), total(&self.den))
+    }
+
+    /// Ingest one input symbol, or return the final value if the input is exhausted.
+    fn ingest(&mut self) -> Option<(isize, isize)> {
+        match self.x.egest() {
+            None => {
+                return Some(self.value_at_one_half());
+            }
+            Some(protocol::Reduction::Amplify) => {
+                self.ingest_amplify();
+            }
+            Some(protocol::Reduction::Uncover) => {
+                self.ingest_uncover();
+            }
+        }
+        self.reduce();
+        None
+    }
+
+    fn ingest_amplify(&mut self) {
+        fn amplify(coefficients: &[isize]) -> Vec<isize> {
+            let n = coefficients.len() - 1;
+            let mut b = bernstein(coefficients);
+            for (i, bi) in b.iter_mut().enumerate() {
+                *bi = bi.checked_mul(1 << (n - i)).expect(support::OVERFLOW);
+            }
+            b
+        }
+        self.num = amplify(&self.num);
+        self.den = amplify(&self.den);
+    }
+
+    fn ingest_uncover(&mut self) {
+        self.num.reverse();
+        self.den.reverse();
+        self.num = bernstein(&self.num);
+        self.den = bernstein(&self.den);
+    }
+
+    /// Halve all coefficients while they are all even.
+    fn reduce(&mut self) {
+        while self
+            .num
+            .iter()
+            .chain(self.den.iter())
+            .all(|c| support::is_even(*c))
+        {
+            for c in self.num.iter_mut().chain(self.den.iter_mut()) {
+                *c /= 2;
+            }
+        }
+    }
+
+    fn reduction_egest(&mut self) -> Result<Option<protocol::Reduction>, isize> {
+        let (nmin, dmin, nmax, dmax) = self.image_extremes();
+        // FIXME: remove sanity checks?
+        if support::not_greater_than_zero(nmin, dmin) {
+            panic!("logic error");
+        }
+        if support::not_less_than_one(nmax, dmax) {
+            panic!("logic error");
+        }
+        if support::less_than_one_half(nmax, dmax) {
+            Ok(Some(self.amplify()))
+        } else if support::greater_than_one_half(nmin, dmin) {
+            Ok(Some(self.uncover()))
+        } else {
+            Err(0)
+        }
+    }
+
+    fn amplify(&mut self) -> protocol::Reduction {
+        if self.den.iter().all(|c| support::is_even(*c)) {
+            for c in self.den.iter_mut() {
+                *c /= 2;
+            }
+        } else {
+            for c in self.num.iter_mut() {
+                *c = c.checked_mul(2).expect(support::OVERFLOW);
+            }
+        }
+        protocol::Reduction::Amplify
+    }
+
+    fn uncover(&mut self) -> protocol::Reduction {
+        self.turn();
+        for i in 0..self.num.len() {
+            self.num[i] = self.num[i]
+                .checked_sub(self.den[i])
+                .expect(support::OVERFLOW);
+        }
+        protocol::Reduction::Uncover
+    }
+}
+
+impl Strategy for Polynomial {
+    fn coefficients(&self) -> Vec<isize> {
+        self.num.iter().chain(self.den.iter()).cloned().collect()
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        vec![&mut self.x]
+    }
+
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        loop {
+            if self.are_singularities_outside_domain() {
+                if let Ok(reduction) = self.reduction_egest() {
+                    return Ok(reduction);
+                }
+            }
+            if let Some((num, den)) = self.ingest() {
+                return match ratio::new_i(num, den) {
+                    (None, None, Some(ratio)) => Err(Box::new(ratio)),
+                    _ => panic!("logic error"),
+                };
+            }
+        }
+    }
+}
@@ -22,6 +22,7 @@ use crate::protocol;
 use crate::strategy::Strategy;
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
 
     use super::*;
@@ -171,6 +172,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::legacy_numeric_constants)]
     fn does_not_overflow() {
         if let (None, None, Some(mut ratio)) = new(true, usize::max_value() - 1, usize::max_value())
         {
@@ -187,6 +189,7 @@ pub struct Ratio {
     den: usize,
 }
 
+#[allow(clippy::needless_return, clippy::redundant_field_names)]
 pub fn new(
     positive: bool,
     num: usize,
@@ -240,12 +243,17 @@ pub fn new_i(
 ) {
     new(
         (num >= 0 && den >= 0) || (num < 0 && den < 0),
-        if num >= 0 { num } else { -num } as usize,
-        if den >= 0 { den } else { -den } as usize,
+        num.unsigned_abs(),
+        den.unsigned_abs(),
     )
 }
 
 impl Strategy for Ratio {
+    fn coefficients(&self) -> Vec<isize> {
+        vec![self.num as isize, self.den as isize]
+    }
+
+    #[allow(clippy::needless_return, clippy::manual_is_multiple_of)]
     fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
         if self.num > self.den / 2 {
             std::mem::swap(&mut self.num, &mut self.den);
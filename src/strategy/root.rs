@@ -0,0 +1,335 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::bigint::BigInt;
+use crate::protocol;
+use crate::strategy::support;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::cmp::Ordering;
+use std::mem::swap;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use crate::Consume;
+
+    fn root(x: Number, q: usize) -> Number {
+        let (special, primer, root) = new(x, q);
+        if let Some(special) = special {
+            return Number::Special(special);
+        }
+        Number::Other(
+            primer,
+            Clog {
+                strategy: Box::new(root.unwrap()),
+            },
+        )
+    }
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    #[test]
+    fn specials() {
+        assert_eq(root(Number::ratio(0, 1), 2), Number::ratio(0, 1));
+        assert_eq(root(Number::ratio(1, 1), 2), Number::ratio(1, 1));
+        assert_eq(root(Number::ratio(-1, 1), 3), Number::ratio(-1, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "even root of negative number")]
+    fn forbids_even_root_of_negative_one() {
+        root(Number::ratio(-1, 1), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "even root of negative number")]
+    fn forbids_even_root_of_negative() {
+        root(Number::ratio(-1, 4), 2);
+    }
+
+    #[test]
+    fn perfect_squares() {
+        assert_eq(root(Number::ratio(1, 4), 2), Number::ratio(1, 2));
+        assert_eq(root(Number::ratio(4, 9), 2), Number::ratio(2, 3));
+        assert_eq(root(Number::ratio(9, 4), 2), Number::ratio(3, 2));
+        assert_eq(root(Number::ratio(49, 1), 2), Number::ratio(7, 1));
+        assert_eq(root(Number::ratio(100, 289), 2), Number::ratio(10, 17));
+    }
+
+    #[test]
+    fn perfect_cubes() {
+        assert_eq(root(Number::ratio(8, 27), 3), Number::ratio(2, 3));
+        assert_eq(root(Number::ratio(-27, 8), 3), Number::ratio(-3, 2));
+        assert_eq(root(Number::ratio(-1, 125), 3), Number::ratio(-1, 5));
+    }
+
+    #[test]
+    fn square_root_of_two() {
+        assert_eq!(
+            Number::compare(root(Number::ratio(2, 1), 2), Number::ratio(14142, 10000)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Number::compare(root(Number::ratio(2, 1), 2), Number::ratio(14143, 10000)),
+            Ordering::Less
+        );
+    }
+
+    /// Whether the first len symbols of the q-th root x of num / den narrow it down correctly.
+    fn assert_symbols(x: Number, q: u32, num: isize, den: isize, len: usize) {
+        let (primer, clog) = x.unwrap_other();
+        let symbols: Vec<protocol::Reduction> = clog.take(len).collect();
+        assert_eq!(symbols.len(), len);
+        let mut consume = Consume::new(Number::from_symbols(primer, symbols));
+        assert_eq!(consume.consume(len), len);
+        // The root lies between the values at the ends of the remaining input.
+        let (nx, n, dx, d) = consume.state();
+        let (num, den) = (BigInt::from(num), BigInt::from(den));
+        let side = |a: &BigInt, b: &BigInt| {
+            let (a, b) = (a.pow(q), b.pow(q));
+            (&(&a * &den) - &(&num * &b)).signum() * b.signum()
+        };
+        assert!(side(n, d) * side(&(n + nx), &(d + dx)) <= 0);
+    }
+
+    #[test]
+    fn long_square_root_of_two() {
+        assert_symbols(root(Number::ratio(2, 1), 2), 2, 2, 1, 200);
+    }
+
+    #[test]
+    fn long_fifth_roots() {
+        assert_symbols(root(Number::ratio(1, 3), 5), 5, 1, 3, 200);
+        assert_symbols(root(Number::ratio(-7, 2), 5), 5, -7, 2, 200);
+    }
+
+    #[test]
+    fn fifth_root_of_one_third() {
+        // 0.80274...
+        assert_eq!(
+            Number::compare(root(Number::ratio(1, 3), 5), Number::ratio(8027, 10000)),
+            Ordering::Greater
+        );
+        assert_eq!(
+            Number::compare(root(Number::ratio(1, 3), 5), Number::ratio(8028, 10000)),
+            Ordering::Less
+        );
+    }
+}
+
+/**
+ * The q-th root _w_ of a value _z_ greater than zero and lesser than one.
+ *
+ * The egested value is tracked as _(nw * w + n) / (dw * w + d)_.
+ * Each output symbol is decided by comparing _w_ against the rational that the egested value
+ * maps to one half, which amounts to comparing _z_ against the q-th power of that rational.
+ * The input is tracked as _(nz * s + m) / (dz * s + e)_ where _s_ is the remaining input.
+ * Both grow with every symbol and are raised to the q-th power, so they are kept unbounded.
+ */
+pub struct Root {
+    z: Clog,
+    q: u32,
+    nz: BigInt,
+    m: BigInt,
+    dz: BigInt,
+    e: BigInt,
+    nw: BigInt,
+    n: BigInt,
+    dw: BigInt,
+    d: BigInt,
+    increasing: bool,
+}
+
+pub fn new(
+    x: Number,
+    q: usize,
+) -> (
+    Option<protocol::Special>,
+    Option<protocol::Primer>,
+    Option<Root>,
+) {
+    if q == 0 {
+        panic!("division by zero");
+    }
+    let even = q.is_multiple_of(2);
+    match x {
+        Number::Special(special) => {
+            if even && special == protocol::Special::NegOne {
+                panic!("even root of negative number");
+            }
+            (Some(special), None, None)
+        }
+        Number::Other(primer, z) => {
+            if even
+                && (primer == Some(protocol::Primer::Reflect)
+                    || primer == Some(protocol::Primer::Ground))
+            {
+                panic!("even root of negative number");
+            }
+            (None, primer, Some(Root::new(z, q)))
+        }
+    }
+}
+
+impl Root {
+    fn new(z: Clog, q: usize) -> Root {
+        let (zero, one) = (BigInt::default(), BigInt::from(1isize));
+        Root {
+            z,
+            q: q as u32,
+            nz: one.clone(),
+            m: zero.clone(),
+            dz: zero.clone(),
+            e: one.clone(),
+            nw: one.clone(),
+            n: zero.clone(),
+            dw: zero,
+            d: one,
+            increasing: true,
+        }
+    }
+
+    fn ingest_amplify(&mut self) {
+        if !self.nz.is_even() || !self.dz.is_even() {
+            self.m = &self.m << 1;
+            self.e = &self.e << 1;
+        } else {
+            self.nz = &self.nz >> 1;
+            self.dz = &self.dz >> 1;
+        }
+    }
+
+    fn ingest_uncover(&mut self) {
+        self.nz = &self.nz + &self.m;
+        self.dz = &self.dz + &self.e;
+        swap(&mut self.nz, &mut self.m);
+        swap(&mut self.dz, &mut self.e);
+    }
+
+    fn input_at_one_half(&self) -> (BigInt, BigInt) {
+        (&self.nz + &(&self.m << 1), &self.dz + &(&self.e << 1))
+    }
+
+    /// The value of _w_ that the egested value maps to one half.
+    fn pivot(&self) -> (BigInt, BigInt) {
+        let num = &self.d - &(&self.n << 1);
+        let den = &(&self.nw << 1) - &self.dw;
+        if den.signum() < 0 {
+            (-num, -den)
+        } else {
+            (num, den)
+        }
+    }
+
+    /// Compare the input against _p / r_, or None if the ingested information is not enough.
+    fn compare_input(&self, p: &BigInt, r: &BigInt) -> Option<Ordering> {
+        let at_zero = (&self.m * r).cmp(&(p * &self.e));
+        let at_one = (&(&self.m + &self.nz) * r).cmp(&(p * &(&self.e + &self.dz)));
+        if at_zero != Ordering::Less && at_one != Ordering::Less {
+            Some(Ordering::Greater)
+        } else if at_zero != Ordering::Greater && at_one != Ordering::Greater {
+            Some(Ordering::Less)
+        } else {
+            None
+        }
+    }
+
+    /// Compare _w_ against _num / den_.
+    fn compare_output(&mut self, num: &BigInt, den: &BigInt) -> Ordering {
+        if num.signum() <= 0 {
+            return Ordering::Greater;
+        }
+        if num >= den {
+            return Ordering::Less;
+        }
+        let p = num.pow(self.q);
+        let r = den.pow(self.q);
+        loop {
+            if let Some(ordering) = self.compare_input(&p, &r) {
+                return ordering;
+            }
+            match self.z.egest() {
+                None => {
+                    let (num, den) = self.input_at_one_half();
+                    return (&num * &r).cmp(&(&p * &den));
+                }
+                Some(protocol::Reduction::Amplify) => {
+                    self.ingest_amplify();
+                }
+                Some(protocol::Reduction::Uncover) => {
+                    self.ingest_uncover();
+                }
+            }
+        }
+    }
+
+    fn uncover(&mut self) -> protocol::Reduction {
+        self.dw = &self.dw - &self.nw;
+        self.d = &self.d - &self.n;
+        swap(&mut self.nw, &mut self.dw);
+        swap(&mut self.n, &mut self.d);
+        self.increasing = !self.increasing;
+        protocol::Reduction::Uncover
+    }
+
+    fn amplify(&mut self) -> protocol::Reduction {
+        if !self.dw.is_even() || !self.d.is_even() {
+            self.nw = &self.nw << 1;
+            self.n = &self.n << 1;
+        } else {
+            self.dw = &self.dw >> 1;
+            self.d = &self.d >> 1;
+        }
+        protocol::Reduction::Amplify
+    }
+}
+
+impl Strategy for Root {
+    fn coefficients(&self) -> Vec<isize> {
+        support::narrowed(&[
+            &self.nz, &self.m, &self.dz, &self.e, &self.nw, &self.n, &self.dw, &self.d,
+        ])
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        vec![&mut self.z]
+    }
+
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        let (num, den) = self.pivot();
+        let ordering = self.compare_output(&num, &den);
+        let ordering = if self.increasing {
+            ordering
+        } else {
+            ordering.reverse()
+        };
+        match ordering {
+            Ordering::Equal => Ok(None),
+            Ordering::Less => Ok(Some(self.amplify())),
+            Ordering::Greater => Ok(Some(self.uncover())),
+        }
+    }
+}
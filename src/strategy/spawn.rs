@@ -0,0 +1,276 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::{Step, Strategy};
+use crate::Clog;
+use crate::Number;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Poll;
+use std::thread;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    #[test]
+    fn specials() {
+        assert_eq(new(Number::ratio(-1, 1)), Number::ratio(-1, 1));
+        assert_eq(new(Number::ratio(0, 1)), Number::ratio(0, 1));
+        assert_eq(new(Number::ratio(1, 1)), Number::ratio(1, 1));
+    }
+
+    #[test]
+    fn others() {
+        for (n, d) in [(-5, 3), (-2, 7), (3, 11), (13, 4)].iter() {
+            assert_eq(new(Number::ratio(*n, *d)), Number::ratio(*n, *d));
+        }
+    }
+
+    #[test]
+    fn infinite() {
+        // Dropping the consumer of an endless producer must not hang.
+        let (_, mut clog) = new(crate::strategy::tests::amplified(usize::MAX)).unwrap_other();
+        for _ in 0..1000 {
+            assert_eq!(clog.egest(), Some(protocol::Reduction::Amplify));
+        }
+    }
+
+    /// A producer that ingests forever without deciding on a symbol.
+    struct Undecided;
+
+    impl Strategy for Undecided {
+        fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+            loop {
+                self.step()?;
+            }
+        }
+
+        fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+            Ok(Step::Ingest)
+        }
+    }
+
+    #[test]
+    fn undecided() {
+        // Dropping the consumer of a producer stuck between symbols must stop its worker.
+        let mut spawn = Spawn::new(Clog {
+            strategy: Box::new(Undecided),
+        });
+        let worker = spawn.worker.take().unwrap();
+        drop(spawn);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send_sync<T: Send + Sync>() {}
+        is_send_sync::<Number>();
+    }
+
+    #[test]
+    fn across_threads() {
+        let x = Number::homographic(Number::ratio(2, 7), 1, 1, 0, 1);
+        let y = thread::spawn(move || Number::homographic(x, 2, 0, 0, 1))
+            .join()
+            .unwrap();
+        assert_eq(y, Number::ratio(18, 7));
+    }
+
+    #[test]
+    fn par_combine() {
+        let x = Number::homographic(Number::ratio(1, 3), 1, 1, 0, 1);
+        let y = Number::homographic(Number::ratio(-2, 5), 1, 0, 1, 1);
+        // (4/3) * (-2/3) - 1
+        assert_eq(
+            Number::par_combine(x, y, 1, 0, 0, -1, 0, 0, 0, 1),
+            Number::ratio(-17, 9),
+        );
+    }
+
+    #[test]
+    fn polls() {
+        let (_, mut clog) = new(Number::ratio(3, 11)).unwrap_other();
+        let mut egested = Vec::new();
+        loop {
+            match clog.poll_egest() {
+                Poll::Ready(Some(reduction)) => egested.push(reduction),
+                Poll::Ready(None) => break,
+                Poll::Pending => thread::yield_now(),
+            }
+        }
+        let (_, expected) = Number::ratio(3, 11).unwrap_other();
+        assert_eq!(egested, expected.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn past_end() {
+        let (_, mut clog) = new(Number::ratio(3, 11)).unwrap_other();
+        while clog.egest().is_some() {}
+        assert_eq!(clog.egest(), None);
+        assert_eq!(clog.next(), None);
+        assert_eq!(clog.poll_egest(), Poll::Ready(None));
+    }
+
+    struct Failing;
+
+    impl Strategy for Failing {
+        fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+            panic!("failing producer");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "failing producer")]
+    fn propagates_panic() {
+        let mut clog = Clog {
+            strategy: Box::new(Spawn::new(Clog {
+                strategy: Box::new(Failing),
+            })),
+        };
+        clog.egest();
+    }
+}
+
+/// Number of symbols a worker may produce ahead of its consumer.
+const BUFFER: usize = 64;
+
+/**
+ * Receive symbols produced by a Clog running in its own thread.
+ *
+ * Once dropped, the worker stops at its next step. Strategies without a step of their own,
+ * such as a Stream waiting on its iterator, take a whole symbol per step,
+ * so a worker blocked within one only stops when that symbol is produced.
+ */
+pub struct Spawn {
+    receiver: Mutex<Receiver<Option<protocol::Reduction>>>,
+    worker: Option<thread::JoinHandle<()>>,
+    /// Set when the consumer is gone, for the worker to stop between ingestions.
+    cancelled: Arc<AtomicBool>,
+    /// Whether the end was received, after which the worker is gone.
+    ended: bool,
+}
+
+/// Evaluate x in a new thread, ahead of its consumer.
+pub fn new(x: Number) -> Number {
+    match x {
+        Number::Special(_) => x,
+        Number::Other(primer, clog) => Number::Other(
+            primer,
+            Clog {
+                strategy: Box::new(Spawn::new(clog)),
+            },
+        ),
+    }
+}
+
+impl Spawn {
+    fn new(mut x: Clog) -> Spawn {
+        let (sender, receiver) = sync_channel(BUFFER);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let is_cancelled = Arc::clone(&cancelled);
+        let worker = thread::spawn(move || loop {
+            // Go step by step, so that a gone consumer is noticed between ingestions.
+            let reduction = match x.strategy.step() {
+                Ok(Step::Ingest) => {
+                    if is_cancelled.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    continue;
+                }
+                Ok(Step::Egest(reduction)) => reduction,
+                Err(next) => {
+                    x.strategy = next;
+                    continue;
+                }
+            };
+            // Stop when the consumer is gone or the stream ends.
+            if sender.send(reduction).is_err() || reduction.is_none() {
+                break;
+            }
+        });
+        Spawn {
+            receiver: Mutex::new(receiver),
+            worker: Some(worker),
+            cancelled,
+            ended: false,
+        }
+    }
+}
+
+impl Spawn {
+    /// Take note of a received end.
+    fn receive(&mut self, reduction: Option<protocol::Reduction>) -> Option<protocol::Reduction> {
+        if reduction.is_none() {
+            self.ended = true;
+        }
+        reduction
+    }
+
+    /// The worker is gone without ending the stream: it panicked.
+    fn resume_panic(&mut self) -> ! {
+        if let Some(worker) = self.worker.take() {
+            if let Err(cause) = worker.join() {
+                panic::resume_unwind(cause);
+            }
+        }
+        panic!("logic error");
+    }
+}
+
+impl Drop for Spawn {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Strategy for Spawn {
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        if self.ended {
+            return Ok(None);
+        }
+        match self.receiver.get_mut().unwrap().recv() {
+            Ok(reduction) => Ok(self.receive(reduction)),
+            Err(_) => self.resume_panic(),
+        }
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        if self.ended {
+            return Ok(Poll::Ready(None));
+        }
+        match self.receiver.get_mut().unwrap().try_recv() {
+            Ok(reduction) => Ok(Poll::Ready(self.receive(reduction))),
+            Err(TryRecvError::Empty) => Ok(Poll::Pending),
+            Err(TryRecvError::Disconnected) => self.resume_panic(),
+        }
+    }
+}
@@ -0,0 +1,149 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::iter::Fuse;
+use std::sync::Mutex;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use crate::protocol::Reduction;
+    use std::cmp::Ordering;
+    use std::iter;
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    fn reductions(x: Number) -> Vec<Reduction> {
+        x.unwrap_other().1.collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        for (n, d, primer) in [
+            (1, 10, None),
+            (3, 1, Some(protocol::Primer::Turn)),
+            (-2, 7, Some(protocol::Primer::Reflect)),
+            (-13, 4, Some(protocol::Primer::Ground)),
+        ]
+        .iter()
+        {
+            let symbols = reductions(Number::ratio(*n, *d));
+            assert_eq(new(*primer, symbols, None), Number::ratio(*n, *d));
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq(new(None, Vec::new(), None), Number::ratio(1, 2));
+        assert_eq(
+            new(Some(protocol::Primer::Ground), Vec::new(), None),
+            Number::ratio(-2, 1),
+        );
+    }
+
+    #[test]
+    fn infinite() {
+        // The endless sequence of Uncover is the golden ratio minus one,
+        // which ratios of consecutive Fibonacci numbers approach from alternating sides.
+        let (mut n, mut d) = (1, 2);
+        for k in 0..20 {
+            let x = new(None, iter::repeat(Reduction::Uncover), None);
+            let y = Number::homographic(x, 1, 1, 0, 1);
+            let expected = if k % 2 == 0 {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+            assert_eq!(Number::compare(y, Number::ratio(n + d, d)), expected);
+            let next = n + d;
+            n = d;
+            d = next;
+        }
+    }
+
+    #[test]
+    fn rational() {
+        let symbols = reductions(Number::ratio(567, 1000));
+        let len = symbols.len();
+        assert_eq(new(None, symbols, Some(len)), Number::ratio(567, 1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "stream exceeds 100 symbols")]
+    fn not_rational() {
+        reductions(new(None, iter::repeat(Reduction::Uncover), Some(100)));
+    }
+}
+
+/// Reduction symbols read from an iterator.
+pub struct Stream<I> {
+    symbols: Mutex<Fuse<I>>,
+    limit: Option<usize>,
+    count: usize,
+}
+
+/// The Number given by an optional primer and a stream of Reduction symbols,
+/// optionally required to end within a number of symbols.
+pub fn new<I>(primer: Option<protocol::Primer>, symbols: I, limit: Option<usize>) -> Number
+where
+    I: IntoIterator<Item = protocol::Reduction>,
+    I::IntoIter: Send + 'static,
+{
+    Number::Other(
+        primer,
+        Clog {
+            strategy: Box::new(Stream {
+                symbols: Mutex::new(symbols.into_iter().fuse()),
+                limit,
+                count: 0,
+            }),
+        },
+    )
+}
+
+impl<I> Strategy for Stream<I>
+where
+    I: Iterator<Item = protocol::Reduction> + Send + 'static,
+{
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        let reduction = self.symbols.get_mut().unwrap().next();
+        if reduction.is_some() {
+            self.count += 1;
+            if let Some(limit) = self.limit {
+                if self.count > limit {
+                    panic!("stream exceeds {} symbols", limit);
+                }
+            }
+        }
+        Ok(reduction)
+    }
+
+    fn name(&self) -> &'static str {
+        "Stream"
+    }
+}
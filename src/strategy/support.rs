@@ -18,6 +18,11 @@
  * along with clarith.  If not, see <http://www.gnu.org/licenses/>
  */
 
+use crate::bigint::BigInt;
+
+/// Panic message of checked arithmetic on coefficients that exceed isize.
+pub const OVERFLOW: &str = "coefficient overflow";
+
 pub fn greater_than_one(n: isize, d: isize) -> bool {
     if d > 0 {
         n > d
@@ -78,7 +83,7 @@ pub fn equal_to_one_half(mut n: isize, mut d: isize) -> bool {
     if d % 2 == 0 {
         d /= 2;
     } else {
-        n = n.checked_mul(2).unwrap();
+        n = n.checked_mul(2).expect(OVERFLOW);
     }
     n == d
 }
@@ -87,7 +92,7 @@ pub fn greater_than_one_half(mut n: isize, mut d: isize) -> bool {
     if d % 2 == 0 {
         d /= 2;
     } else {
-        n = n.checked_mul(2).unwrap();
+        n = n.checked_mul(2).expect(OVERFLOW);
     }
     if d > 0 {
         n > d
@@ -102,7 +107,7 @@ pub fn less_than_one_half(mut n: isize, mut d: isize) -> bool {
     if d % 2 == 0 {
         d /= 2;
     } else {
-        n = n.checked_mul(2).unwrap();
+        n = n.checked_mul(2).expect(OVERFLOW);
     }
     if d > 0 {
         n < d
@@ -155,16 +160,16 @@ pub fn updated_range(
     }
 
     if dmin < 0 {
-        nmin *= -1;
-        dmin *= -1;
+        nmin = nmin.checked_neg().expect(OVERFLOW);
+        dmin = dmin.checked_neg().expect(OVERFLOW);
     }
     if dmax < 0 {
-        nmax *= -1;
-        dmax *= -1;
+        nmax = nmax.checked_neg().expect(OVERFLOW);
+        dmax = dmax.checked_neg().expect(OVERFLOW);
     }
     if dnew < 0 {
-        nnew *= -1;
-        dnew *= -1;
+        nnew = nnew.checked_neg().expect(OVERFLOW);
+        dnew = dnew.checked_neg().expect(OVERFLOW);
     }
 
     if nmin == 0 {
@@ -201,8 +206,8 @@ pub fn compare_reduce(n0: isize, d0: isize, n1: isize, d1: isize) -> (isize, isi
     (
         // (n0 * d0.signum()).checked_mul(d1.abs()).unwrap(),
         // (n1 * d1.signum()).checked_mul(d0.abs()).unwrap(),
-        n0.checked_mul(d1).unwrap(),
-        n1.checked_mul(d0).unwrap(),
+        n0.checked_mul(d1).expect(OVERFLOW),
+        n1.checked_mul(d0).expect(OVERFLOW),
     )
 }
 
@@ -229,3 +234,85 @@ pub fn equal_to_one(n: isize, d: isize) -> bool {
 pub fn equal_to_minus_one(n: isize, d: isize) -> bool {
     d != 0 && n == -d
 }
+
+pub fn gcd(a: isize, b: isize) -> isize {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Number of ingestions between attempts to divide coefficients by their gcd.
+pub const REDUCTION_PERIOD: usize = 8;
+
+/// Divide all coefficients by their greatest common divisor; tell whether it was greater than one.
+pub fn reduce(c: &mut [isize]) -> bool {
+    let g = c.iter().fold(0, |g, x| gcd(g, *x));
+    if g > 1 {
+        for x in c.iter_mut() {
+            *x /= g;
+        }
+    }
+    g > 1
+}
+
+/// Compute _a * b + c * d_.
+pub fn dot(a: isize, b: isize, c: isize, d: isize) -> isize {
+    a.checked_mul(b)
+        .expect(OVERFLOW)
+        .checked_add(c.checked_mul(d).expect(OVERFLOW))
+        .expect(OVERFLOW)
+}
+
+/// The coefficients of _m1(m2(x))_, divided by their greatest common divisor.
+pub fn compose(m1: [isize; 4], m2: [isize; 4]) -> [isize; 4] {
+    let mut m = [
+        dot(m1[0], m2[0], m1[1], m2[2]),
+        dot(m1[0], m2[1], m1[1], m2[3]),
+        dot(m1[2], m2[0], m1[3], m2[2]),
+        dot(m1[2], m2[1], m1[3], m2[3]),
+    ];
+    reduce(&mut m);
+    m
+}
+
+/// The coefficients of a combine after substituting x by _(p * x + q) / (r * x + s)_,
+/// divided by their greatest common divisor.
+pub fn substitute_x(m: [isize; 8], [p, q, r, s]: [isize; 4]) -> [isize; 8] {
+    let mut fused = [0; 8];
+    for &k in [0, 4].iter() {
+        fused[k] = dot(m[k], p, m[k + 2], r);
+        fused[k + 1] = dot(m[k + 1], p, m[k + 3], r);
+        fused[k + 2] = dot(m[k], q, m[k + 2], s);
+        fused[k + 3] = dot(m[k + 1], q, m[k + 3], s);
+    }
+    reduce(&mut fused);
+    fused
+}
+
+/// The coefficients of a combine after substituting y by _(p * y + q) / (r * y + s)_,
+/// divided by their greatest common divisor.
+pub fn substitute_y(m: [isize; 8], [p, q, r, s]: [isize; 4]) -> [isize; 8] {
+    let mut fused = [0; 8];
+    for &k in [0, 4].iter() {
+        fused[k] = dot(m[k], p, m[k + 1], r);
+        fused[k + 1] = dot(m[k], q, m[k + 1], s);
+        fused[k + 2] = dot(m[k + 2], p, m[k + 3], r);
+        fused[k + 3] = dot(m[k + 2], q, m[k + 3], s);
+    }
+    reduce(&mut fused);
+    fused
+}
+
+pub fn are_within(c: &[isize], limit: isize) -> bool {
+    c.iter().all(|x| x.abs() <= limit)
+}
+
+/// Coefficients as isize for instrumentation, or none if any does not fit.
+pub fn narrowed(c: &[&BigInt]) -> Vec<isize> {
+    c.iter()
+        .map(|c| c.to_isize())
+        .collect::<Option<Vec<isize>>>()
+        .unwrap_or_default()
+}
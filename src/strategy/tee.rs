@@ -0,0 +1,257 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::Strategy;
+use crate::Clog;
+use crate::Number;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Poll;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn forks_special() {
+        let (x, y) = new(Number::ratio(-1, 1));
+        assert_eq!(x.unwrap_special(), protocol::Special::NegOne);
+        assert_eq!(y.unwrap_special(), protocol::Special::NegOne);
+    }
+
+    #[test]
+    fn forks_primer() {
+        let (x, y) = new(Number::ratio(-3, 1));
+        assert_eq!(x.unwrap_other().0, Some(protocol::Primer::Ground));
+        assert_eq!(y.unwrap_other().0, Some(protocol::Primer::Ground));
+    }
+
+    #[test]
+    fn forks_reductions() {
+        let (x, y) = new(Number::ratio(567, 1000));
+        let (_, mut cx) = x.unwrap_other();
+        let (_, mut cy) = y.unwrap_other();
+        let mut sx = Vec::new();
+        while let Some(r) = cx.egest() {
+            sx.push(r);
+        }
+        let mut sy = Vec::new();
+        while let Some(r) = cy.egest() {
+            sy.push(r);
+        }
+        assert!(!sx.is_empty());
+        assert_eq!(sx, sy);
+        assert_eq!(cx.egest(), None);
+        assert_eq!(cy.egest(), None);
+    }
+
+    #[test]
+    fn forks_interleaved() {
+        let (x, y) = new(Number::ratio(2953, 7829));
+        let (y, z) = new(y);
+        assert_eq!(
+            Number::compare(x, Number::ratio(2953, 7829)),
+            Ordering::Equal
+        );
+        assert_eq!(Number::compare(y, z), Ordering::Equal);
+    }
+
+    #[test]
+    fn bounded_history() {
+        let (_, clog) = crate::strategy::tests::amplified(100_000).unwrap_other();
+        let mut x = Tee::new(clog);
+        let mut y = x.fork();
+        for _ in 0..100_000 {
+            assert_eq!(x.egest().ok(), Some(Some(protocol::Reduction::Amplify)));
+            assert_eq!(x.source.lock().unwrap().history.len(), 1);
+            assert_eq!(y.egest().ok(), Some(Some(protocol::Reduction::Amplify)));
+            assert_eq!(x.source.lock().unwrap().history.len(), 0);
+        }
+        assert_eq!(x.egest().ok(), Some(None));
+        assert_eq!(y.egest().ok(), Some(None));
+    }
+
+    #[test]
+    fn drops_history_of_dropped_reader() {
+        let (_, clog) = crate::strategy::tests::amplified(1000).unwrap_other();
+        let mut x = Tee::new(clog);
+        let y = x.fork();
+        for _ in 0..10 {
+            assert!(x.egest().is_ok());
+        }
+        assert_eq!(x.source.lock().unwrap().history.len(), 10);
+        drop(y);
+        assert!(x.egest().is_ok());
+        assert_eq!(x.source.lock().unwrap().history.len(), 0);
+        let mut z = x.fork();
+        assert!(x.egest().is_ok());
+        assert_eq!(x.source.lock().unwrap().history.len(), 1);
+        assert!(z.egest().is_ok());
+        assert_eq!(x.source.lock().unwrap().history.len(), 0);
+    }
+}
+
+struct Source {
+    x: Clog,
+    /// Symbols recorded for readers behind the fastest one.
+    history: VecDeque<protocol::Reduction>,
+    /// Position of the first symbol in history.
+    base: usize,
+    /// Number of readers at each position; history before the first one is dropped.
+    readers: BTreeMap<usize, usize>,
+    exhausted: bool,
+}
+
+impl Source {
+    fn enter(&mut self, position: usize) {
+        *self.readers.entry(position).or_insert(0) += 1;
+    }
+
+    fn leave(&mut self, position: usize) {
+        let count = self.readers.get_mut(&position).expect("logic error");
+        *count -= 1;
+        if *count == 0 {
+            self.readers.remove(&position);
+        }
+        let first = self.readers.keys().next().copied().unwrap_or(position);
+        while self.base < first && self.history.pop_front().is_some() {
+            self.base += 1;
+        }
+    }
+}
+
+/// A reader of a Clog whose symbols are shared among several readers.
+pub struct Tee {
+    source: Arc<Mutex<Source>>,
+    position: usize,
+}
+
+/**
+ * Split a Number into two Numbers of identical value.
+ *
+ * The input Clog is egested at most once;
+ * its symbols are recorded until the slower reader has replayed them.
+ */
+pub fn new(x: Number) -> (Number, Number) {
+    match x {
+        Number::Special(special) => (Number::Special(special), Number::Special(special)),
+        Number::Other(primer, clog) => {
+            let tee = Tee::new(clog);
+            let fork = tee.fork();
+            (
+                Number::Other(
+                    primer,
+                    Clog {
+                        strategy: Box::new(tee),
+                    },
+                ),
+                Number::Other(
+                    primer,
+                    Clog {
+                        strategy: Box::new(fork),
+                    },
+                ),
+            )
+        }
+    }
+}
+
+impl Tee {
+    fn new(x: Clog) -> Tee {
+        let mut source = Source {
+            x,
+            history: VecDeque::new(),
+            base: 0,
+            readers: BTreeMap::new(),
+            exhausted: false,
+        };
+        source.enter(0);
+        Tee {
+            source: Arc::new(Mutex::new(source)),
+            position: 0,
+        }
+    }
+
+    fn fork(&self) -> Tee {
+        self.source.lock().unwrap().enter(self.position);
+        Tee {
+            source: Arc::clone(&self.source),
+            position: self.position,
+        }
+    }
+
+    /// Read the next symbol, taking it from the source Clog with ingest if not yet recorded.
+    fn egest_with<F>(&mut self, ingest: F) -> Poll<Option<protocol::Reduction>>
+    where
+        F: FnOnce(&mut Clog) -> Poll<Option<protocol::Reduction>>,
+    {
+        let mut source = self.source.lock().unwrap();
+        if self.position == source.base + source.history.len() {
+            if source.exhausted {
+                return Poll::Ready(None);
+            }
+            match ingest(&mut source.x) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(None) => {
+                    source.exhausted = true;
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Some(reduction)) => {
+                    source.history.push_back(reduction);
+                }
+            }
+        }
+        let reduction = source.history[self.position - source.base];
+        source.enter(self.position + 1);
+        source.leave(self.position);
+        self.position += 1;
+        Poll::Ready(Some(reduction))
+    }
+}
+
+impl Drop for Tee {
+    fn drop(&mut self) {
+        // The source may be poisoned by a panic of another reader.
+        let mut source = self
+            .source
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        source.leave(self.position);
+    }
+}
+
+impl Strategy for Tee {
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        match self.egest_with(|x| Poll::Ready(x.egest())) {
+            Poll::Ready(reduction) => Ok(reduction),
+            Poll::Pending => panic!("logic error"),
+        }
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        Ok(self.egest_with(Clog::poll_egest))
+    }
+}
@@ -0,0 +1,331 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::strategy::{Step, Strategy};
+use crate::trace::{Event, Tracer};
+use crate::Clog;
+use std::any::Any;
+use std::mem::replace;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Poll;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::protocol::Reduction;
+    use crate::Number;
+
+    fn record(clog: &mut Clog) -> Arc<Mutex<Vec<String>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        clog.set_tracer(move |event: &Event| sink.lock().unwrap().push(format!("{:?}", event)));
+        events
+    }
+
+    #[test]
+    fn ratio() {
+        let (_, mut clog) = Number::ratio(1, 3).unwrap_other();
+        let events = record(&mut clog);
+        while clog.egest().is_some() {}
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "Coefficients(\"Ratio\", [2, 3])",
+                "Egest(Some(Amplify))",
+                "Coefficients(\"Ratio\", [1, 2])",
+                "Egest(Some(Uncover))",
+                "Coefficients(\"Ratio\", [1, 2])",
+                "Egest(None)",
+            ]
+        );
+    }
+
+    #[test]
+    fn homographic() {
+        let (_, mut clog) = Number::homographic(Number::ratio(1, 3), 1, 0, 1, 1).unwrap_other();
+        let events = record(&mut clog);
+        let mut egested = Vec::new();
+        while let Some(reduction) = clog.egest() {
+            egested.push(reduction);
+        }
+        let events = events.lock().unwrap();
+        // x / (x + 1) at one third is one fourth.
+        assert_eq!(egested, vec![Reduction::Amplify]);
+        assert!(events.contains(&"Ingest(0, None)".to_string()));
+        assert!(events.contains(&"Switch(\"Ratio\")".to_string()));
+        assert_eq!(events.last().unwrap(), "Egest(None)");
+    }
+
+    #[test]
+    fn steps() {
+        let (_, mut clog) = Number::combine(
+            Number::ratio(1, 3),
+            Number::ratio(2, 5),
+            0,
+            1,
+            1,
+            0,
+            0,
+            0,
+            0,
+            2,
+        )
+        .unwrap_other();
+        let events = record(&mut clog);
+        while clog.egest().is_some() {}
+        let events = events.lock().unwrap();
+        // Every step, be it an ingestion or an egestion, reports coefficients and image.
+        let steps = events
+            .iter()
+            .filter(|e| e.starts_with("Coefficients(\"Combine\""))
+            .count();
+        let egests = events
+            .iter()
+            .take_while(|e| !e.starts_with("Switch"))
+            .filter(|e| e.starts_with("Egest"))
+            .count();
+        assert!(steps > egests);
+        assert!(events.iter().any(|e| e.starts_with("Image([")));
+    }
+
+    fn symbols(x: Number) -> usize {
+        let (_, mut clog) = x.unwrap_other();
+        let mut count = 0;
+        while clog.egest().is_some() {
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn profile() {
+        fn x() -> Number {
+            Number::combine(
+                Number::ratio(1, 3),
+                Number::ratio(2, 5),
+                0,
+                1,
+                1,
+                0,
+                0,
+                0,
+                0,
+                1,
+            )
+        }
+        let (_, mut clog) = x().unwrap_other();
+        let profile = clog.profile();
+        let events = record(&mut clog);
+        while clog.egest().is_some() {}
+        let stats = profile.stats();
+        assert!(stats.ingested.iter().sum::<usize>() > 0);
+        assert_eq!(stats.egested, symbols(Number::ratio(11, 15)));
+        assert_eq!(stats.transitions.last(), Some(&"Ratio"));
+        assert!(stats.max_coefficient_bits > 0);
+        assert!(events
+            .lock()
+            .unwrap()
+            .contains(&"Switch(\"Ratio\")".to_string()));
+    }
+}
+
+/// Which events a Traced strategy reports.
+#[derive(Clone, Copy)]
+enum Role {
+    Output,
+    Input(usize),
+}
+
+/// Report events of a wrapped strategy to a tracer.
+pub struct Traced {
+    inner: Box<dyn Strategy>,
+    tracer: Arc<Mutex<dyn Tracer>>,
+    role: Role,
+    /// Reductions of the wrapped strategy already reported.
+    reductions: usize,
+}
+
+/// Strategy left in place while a Clog is being wrapped.
+struct Detached;
+
+impl Strategy for Detached {
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        panic!("logic error");
+    }
+}
+
+/// Report all events of a Clog, and the ingestions from its inputs, to tracer.
+pub fn attach(clog: &mut Clog, tracer: Arc<Mutex<dyn Tracer>>) {
+    let inner = replace(&mut clog.strategy, Box::new(Detached));
+    clog.strategy = Traced::wrap(inner, tracer, Role::Output);
+}
+
+impl Traced {
+    fn wrap(
+        mut inner: Box<dyn Strategy>,
+        tracer: Arc<Mutex<dyn Tracer>>,
+        role: Role,
+    ) -> Box<dyn Strategy> {
+        if let Role::Output = role {
+            for (i, input) in inner.inputs().into_iter().enumerate() {
+                if !Traced::is_traced_by(&*input.strategy, &tracer) {
+                    let strategy = replace(&mut input.strategy, Box::new(Detached));
+                    input.strategy = Traced::wrap(strategy, Arc::clone(&tracer), Role::Input(i));
+                }
+            }
+        }
+        Box::new(Traced {
+            inner,
+            tracer,
+            role,
+            reductions: 0,
+        })
+    }
+
+    fn is_traced_by(mut strategy: &dyn Strategy, tracer: &Arc<Mutex<dyn Tracer>>) -> bool {
+        while let Some(traced) = (strategy as &dyn Any).downcast_ref::<Traced>() {
+            if Arc::as_ptr(&traced.tracer) as *const () == Arc::as_ptr(tracer) as *const () {
+                return true;
+            }
+            strategy = &*traced.inner;
+        }
+        false
+    }
+}
+
+impl Traced {
+    /// Report the state of the strategy after a step, for the traced Clog.
+    fn report_state(&mut self, tracer: &mut dyn Tracer) {
+        let reductions = self.inner.reductions();
+        if reductions > self.reductions {
+            tracer.trace(&Event::Reduced(reductions - self.reductions));
+            self.reductions = reductions;
+        }
+        let coefficients = self.inner.coefficients();
+        if !coefficients.is_empty() {
+            tracer.trace(&Event::Coefficients(self.inner.name(), &coefficients));
+        }
+        let image = self.inner.image();
+        if !image.is_empty() {
+            tracer.trace(&Event::Image(&image));
+        }
+    }
+
+    fn report_egest(&mut self, reduction: Option<protocol::Reduction>) {
+        let tracer = Arc::clone(&self.tracer);
+        let mut tracer = tracer.lock().unwrap();
+        match self.role {
+            Role::Output => {
+                self.report_state(&mut *tracer);
+                tracer.trace(&Event::Egest(reduction));
+            }
+            Role::Input(i) => tracer.trace(&Event::Ingest(i, reduction)),
+        }
+    }
+
+    fn report_switch(&mut self, next: Box<dyn Strategy>) -> Box<dyn Strategy> {
+        if let Role::Output = self.role {
+            let tracer = Arc::clone(&self.tracer);
+            let mut tracer = tracer.lock().unwrap();
+            self.report_state(&mut *tracer);
+            tracer.trace(&Event::Switch(next.name()));
+        }
+        Traced::wrap(next, Arc::clone(&self.tracer), self.role)
+    }
+}
+
+impl Strategy for Traced {
+    fn egest(&mut self) -> Result<Option<protocol::Reduction>, Box<dyn Strategy>> {
+        if let Role::Input(_) = self.role {
+            return match self.inner.egest() {
+                Ok(reduction) => {
+                    self.report_egest(reduction);
+                    Ok(reduction)
+                }
+                Err(next) => Err(self.report_switch(next)),
+            };
+        }
+        // Go step by step, so that the state after every ingestion is reported.
+        loop {
+            match self.inner.step() {
+                Ok(Step::Ingest) => {
+                    let tracer = Arc::clone(&self.tracer);
+                    self.report_state(&mut *tracer.lock().unwrap());
+                }
+                Ok(Step::Egest(reduction)) => {
+                    self.report_egest(reduction);
+                    return Ok(reduction);
+                }
+                Err(next) => return Err(self.report_switch(next)),
+            }
+        }
+    }
+
+    fn poll_egest(&mut self) -> Result<Poll<Option<protocol::Reduction>>, Box<dyn Strategy>> {
+        match self.inner.poll_egest() {
+            Ok(Poll::Ready(reduction)) => {
+                self.report_egest(reduction);
+                Ok(Poll::Ready(reduction))
+            }
+            Ok(Poll::Pending) => Ok(Poll::Pending),
+            Err(next) => Err(self.report_switch(next)),
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, Box<dyn Strategy>> {
+        match self.inner.step() {
+            Ok(Step::Ingest) => {
+                if let Role::Output = self.role {
+                    let tracer = Arc::clone(&self.tracer);
+                    self.report_state(&mut *tracer.lock().unwrap());
+                }
+                Ok(Step::Ingest)
+            }
+            Ok(Step::Egest(reduction)) => {
+                self.report_egest(reduction);
+                Ok(Step::Egest(reduction))
+            }
+            Err(next) => Err(self.report_switch(next)),
+        }
+    }
+
+    fn image(&self) -> Vec<isize> {
+        self.inner.image()
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn coefficients(&self) -> Vec<isize> {
+        self.inner.coefficients()
+    }
+
+    fn reductions(&self) -> usize {
+        self.inner.reductions()
+    }
+
+    fn inputs(&mut self) -> Vec<&mut Clog> {
+        self.inner.inputs()
+    }
+}
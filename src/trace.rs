@@ -0,0 +1,142 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+//! Opt-in instrumentation of Clogs.
+
+use crate::protocol;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::protocol::Reduction;
+
+    #[test]
+    fn record() {
+        let mut stats = Stats::default();
+        stats.record(&Event::Ingest(1, Some(Reduction::Amplify)));
+        stats.record(&Event::Ingest(1, Some(Reduction::Uncover)));
+        stats.record(&Event::Ingest(0, None));
+        stats.record(&Event::Coefficients("Combine", &[3, -1024, 0, 7]));
+        stats.record(&Event::Reduced(2));
+        stats.record(&Event::Egest(Some(Reduction::Amplify)));
+        stats.record(&Event::Switch("Homographic"));
+        stats.record(&Event::Egest(None));
+        assert_eq!(
+            stats,
+            Stats {
+                ingested: vec![0, 2],
+                egested: 1,
+                transitions: vec!["Homographic"],
+                max_coefficient_bits: 11,
+                reductions: 2,
+            }
+        );
+    }
+}
+
+/// Something that happened in a traced Clog.
+#[derive(Debug, PartialEq)]
+pub enum Event<'a> {
+    /// The strategy took a symbol from its input of given index; None means that input ended.
+    Ingest(usize, Option<protocol::Reduction>),
+    /// The Clog produced a symbol; None means it ended.
+    Egest(Option<protocol::Reduction>),
+    /// The Clog switched to the named strategy.
+    Switch(&'static str),
+    /// Coefficients of the named strategy after each step, or when switching away.
+    Coefficients(&'static str, &'a [isize]),
+    /// Bounds _nmin, dmin, nmax, dmax_ of the values still to be produced, after each step.
+    Image(&'a [isize]),
+    /// The strategy divided its coefficients by a common factor this many times during the last step.
+    Reduced(usize),
+}
+
+/// Receiver of events from a traced Clog.
+pub trait Tracer: Send {
+    fn trace(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event) + Send> Tracer for F {
+    fn trace(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Work counters of a Clog.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// Symbols ingested from each input, by input index.
+    pub ingested: Vec<usize>,
+    /// Symbols egested.
+    pub egested: usize,
+    /// Names of the strategies switched to, in order.
+    pub transitions: Vec<&'static str>,
+    /// Largest bit length of a coefficient, sampled after every step
+    /// and when the strategy switches.
+    pub max_coefficient_bits: u32,
+    /// Times the coefficients were divided by a common factor.
+    pub reductions: usize,
+}
+
+impl Stats {
+    pub fn record(&mut self, event: &Event) {
+        match event {
+            Event::Ingest(input, Some(_)) => {
+                if self.ingested.len() <= *input {
+                    self.ingested.resize(input + 1, 0);
+                }
+                self.ingested[*input] += 1;
+            }
+            Event::Ingest(_, None) => {}
+            Event::Egest(Some(_)) => self.egested += 1,
+            Event::Egest(None) => {}
+            Event::Switch(name) => self.transitions.push(name),
+            Event::Image(_) => {}
+            Event::Reduced(count) => self.reductions += count,
+            Event::Coefficients(_, coefficients) => {
+                for c in coefficients.iter() {
+                    let bits = usize::BITS - c.unsigned_abs().leading_zeros();
+                    self.max_coefficient_bits = self.max_coefficient_bits.max(bits);
+                }
+            }
+        }
+    }
+}
+
+/// Handle to the work counters of a profiled Clog.
+pub struct Profile(Arc<Mutex<Stats>>);
+
+impl Profile {
+    pub fn new() -> (Profile, impl Tracer) {
+        let stats = Arc::new(Mutex::new(Stats::default()));
+        let sink = Arc::clone(&stats);
+        (Profile(stats), move |event: &Event| {
+            sink.lock().unwrap().record(event)
+        })
+    }
+
+    /// A snapshot of the counters so far.
+    pub fn stats(&self) -> Stats {
+        self.0.lock().unwrap().clone()
+    }
+}
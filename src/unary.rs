@@ -0,0 +1,149 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use crate::protocol;
+use crate::Number;
+
+#[cfg(test)]
+#[allow(clippy::items_after_test_module)]
+mod tests {
+
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn values() -> Vec<(isize, isize)> {
+        vec![
+            (-2, 1),
+            (-1, 1),
+            (-2, 3),
+            (-1, 2),
+            (-1, 4),
+            (0, 1),
+            (1, 4),
+            (1, 2),
+            (2, 3),
+            (1, 1),
+            (2, 1),
+        ]
+    }
+
+    fn assert_eq(n1: Number, n2: Number) {
+        assert_eq!(Number::compare(n1, n2), Ordering::Equal);
+    }
+
+    #[test]
+    fn t_neg() {
+        for (n, d) in values() {
+            assert_eq(neg(Number::ratio(n, d)), Number::ratio(-n, d));
+        }
+    }
+
+    #[test]
+    fn t_abs() {
+        for (n, d) in values() {
+            assert_eq(abs(Number::ratio(n, d)), Number::ratio(n.abs(), d));
+        }
+    }
+
+    #[test]
+    fn t_signum() {
+        for (n, d) in values() {
+            assert_eq(signum(Number::ratio(n, d)), Number::ratio(n.signum(), 1));
+        }
+    }
+
+    #[test]
+    fn t_recip() {
+        for (n, d) in values() {
+            if n != 0 {
+                assert_eq(recip(Number::ratio(n, d)), Number::ratio(d, n));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn p_recip() {
+        recip(Number::ratio(0, 1));
+    }
+
+    #[test]
+    fn keeps_clog() {
+        let x = Number::homographic(Number::ratio(1, 3), 1, 1, 0, 1);
+        assert_eq(neg(recip(x)), Number::ratio(-3, 4));
+    }
+}
+
+pub fn neg(x: Number) -> Number {
+    match x {
+        Number::Special(special) => Number::Special(match special {
+            protocol::Special::NegOne => protocol::Special::PosOne,
+            protocol::Special::Zero => protocol::Special::Zero,
+            protocol::Special::PosOne => protocol::Special::NegOne,
+        }),
+        Number::Other(primer, clog) => Number::Other(
+            match primer {
+                None => Some(protocol::Primer::Reflect),
+                Some(protocol::Primer::Turn) => Some(protocol::Primer::Ground),
+                Some(protocol::Primer::Reflect) => None,
+                Some(protocol::Primer::Ground) => Some(protocol::Primer::Turn),
+            },
+            clog,
+        ),
+    }
+}
+
+pub fn abs(x: Number) -> Number {
+    match x {
+        Number::Special(protocol::Special::NegOne) => Number::Special(protocol::Special::PosOne),
+        Number::Other(Some(protocol::Primer::Reflect), clog) => Number::Other(None, clog),
+        Number::Other(Some(protocol::Primer::Ground), clog) => {
+            Number::Other(Some(protocol::Primer::Turn), clog)
+        }
+        _ => x,
+    }
+}
+
+pub fn signum(x: Number) -> Number {
+    Number::Special(match x {
+        Number::Special(protocol::Special::NegOne) => protocol::Special::NegOne,
+        Number::Special(protocol::Special::Zero) => protocol::Special::Zero,
+        Number::Special(protocol::Special::PosOne) => protocol::Special::PosOne,
+        Number::Other(Some(protocol::Primer::Reflect), _) => protocol::Special::NegOne,
+        Number::Other(Some(protocol::Primer::Ground), _) => protocol::Special::NegOne,
+        Number::Other(_, _) => protocol::Special::PosOne,
+    })
+}
+
+pub fn recip(x: Number) -> Number {
+    match x {
+        Number::Special(protocol::Special::Zero) => panic!("division by zero"),
+        Number::Special(_) => x,
+        Number::Other(primer, clog) => Number::Other(
+            match primer {
+                None => Some(protocol::Primer::Turn),
+                Some(protocol::Primer::Turn) => None,
+                Some(protocol::Primer::Reflect) => Some(protocol::Primer::Ground),
+                Some(protocol::Primer::Ground) => Some(protocol::Primer::Reflect),
+            },
+            clog,
+        ),
+    }
+}
@@ -0,0 +1,173 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn clarith(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_clarith"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_calculate_argument() {
+    let output = clarith(&["-p", "5", "-s", "1/3", "+", "1/2"], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "0.83333...\nUAAUAH\n= 5/6\n");
+}
+
+#[test]
+fn test_calculate_exact() {
+    let output = clarith(&["(0.5 - 2^-2) * 3"], "");
+    assert_eq!(stdout(&output), "0.75\n= 3/4\n");
+    let output = clarith(&["-(6/3)^3"], "");
+    assert_eq!(stdout(&output), "-8\n");
+}
+
+#[test]
+fn test_calculate_irrational() {
+    let output = clarith(&["-p", "4", "sqrt(2) * 3"], "");
+    assert_eq!(stdout(&output), "4.2426...\n");
+}
+
+#[test]
+fn test_calculate_overflow() {
+    // An overflow limits the precision, but is no error.
+    let output = clarith(&["(2/3)^40"], "");
+    assert!(output.status.success());
+    assert!(stdout(&output).ends_with("... (precision limited by coefficient overflow)\n"));
+}
+
+#[test]
+fn test_calculate_overflow_error() {
+    // An overflow before any output is an error, named as such.
+    let output = clarith(&["2^1000"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: coefficient overflow\n"
+    );
+}
+
+#[test]
+fn test_calculate_stdin() {
+    let output = clarith(&[], "1/4\n\n1/0\n2*(\n7\n");
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "0.25\n= 1/4\nerror: division by zero\nerror: unexpected end of expression at column 4\n7\n"
+    );
+}
+
+#[test]
+fn test_calculate_error() {
+    let output = clarith(&["1 +* 2"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: unexpected '*' at column 4\n"
+    );
+}
+
+#[test]
+fn test_encode() {
+    assert_eq!(stdout(&clarith(&["encode", "5"], "")), "TAAUAH\n");
+    assert_eq!(stdout(&clarith(&["encode", "-1/4"], "")), "RAH\n");
+    assert_eq!(stdout(&clarith(&["encode", "1/2"], "")), "H\n");
+    assert_eq!(stdout(&clarith(&["encode", "0"], "")), "Z\n");
+}
+
+#[test]
+fn test_decode() {
+    assert_eq!(stdout(&clarith(&["decode", "TAAUAH"], "")), "5\n");
+    assert_eq!(
+        stdout(&clarith(&["decode", "GAUUUAUUUAAUUUU"], "")),
+        "-157/50\n"
+    );
+    assert_eq!(stdout(&clarith(&["decode", "N"], "")), "-1\n");
+    let output = clarith(&["decode", "TAHA"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: unexpected 'A' after end of symbols\n"
+    );
+}
+
+#[test]
+fn test_compare() {
+    assert_eq!(stdout(&clarith(&["compare", "1/3", "0.333"], "")), ">\n");
+    assert_eq!(stdout(&clarith(&["compare", "1/3", "2/6"], "")), "=\n");
+    assert_eq!(stdout(&clarith(&["compare", "sqrt(2)", "1.5"], "")), "<\n");
+}
+
+#[test]
+fn test_homographic() {
+    let output = clarith(&["-s", "homographic", "2", "1", "0", "1", "1"], "");
+    assert_eq!(stdout(&output), "0.66666666666666666666...\nUH\n= 2/3\n");
+}
+
+#[test]
+fn test_combine() {
+    let output = clarith(
+        &[
+            "combine", "1/3", "2/5", "0", "1", "1", "0", "0", "0", "0", "1",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "0.73333333333333333333...\n= 11/15\n");
+    let output = clarith(&["combine", "1/3", "2/5"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_trace() {
+    let output = clarith(&["-t", "1/3 + 2/5"], "");
+    assert!(output.status.success());
+    let stdout = stdout(&output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "(steps after priming; priming is not traced)");
+    assert!(lines[1].starts_with(" step  strategy"));
+    assert!(lines[2].contains("Homographic  egest U"));
+    assert!(lines[2].ends_with("[4, 1, 2, 5]                             1/5 .. 5/7"));
+    assert!(lines.iter().any(|line| line.contains("ingest x A")));
+    assert!(lines
+        .iter()
+        .any(|line| line.contains("x ended, switch to Ratio")));
+    assert_eq!(
+        &lines[lines.len() - 2..],
+        ["0.73333333333333333333...", "= 11/15"]
+    );
+}
@@ -114,6 +114,66 @@ fn compare_combines() {
     }
 }
 
+#[test]
+fn compare_additions() {
+    compare_combine_grid([0, 1, 1, 0, 0, 0, 0, 1], 7);
+}
+
+#[test]
+fn compare_subtractions() {
+    compare_combine_grid([0, 1, -1, 0, 0, 0, 0, 1], 7);
+}
+
+#[test]
+fn compare_scaled_sums() {
+    // (2 * x - 3 * y + 1) / 5, (x + y) / (x - y) and (x + 1) * (y + 1).
+    compare_combine_grid([0, 2, -3, 1, 0, 0, 0, 5], 5);
+    compare_combine_grid([0, 1, 1, 0, 0, 1, -1, 0], 5);
+    compare_combine_grid([1, 1, 1, 1, 0, 0, 0, 1], 5);
+}
+
+#[test]
+fn compare_combines_singular_in_domain() {
+    // Forms with a zero or a pole for inputs between zero and one,
+    // as signalled by unequal or null signs at the corners of that domain.
+    fn in_domain(mxy: isize, mx: isize, my: isize, m: isize) -> bool {
+        let s = m.signum();
+        s == 0
+            || (m + mx).signum() != s
+            || (m + my).signum() != s
+            || (m + mx + my + mxy).signum() != s
+    }
+    let inputs = vec![
+        (1, 5),
+        (1, 4),
+        (1, 3),
+        (2, 5),
+        (1, 2),
+        (3, 5),
+        (2, 3),
+        (3, 4),
+        (4, 5),
+    ];
+    let range = 1;
+    let mut forms = 0;
+    for c in 0..(2 * range + 1isize).pow(8) {
+        let c: Vec<isize> = (0..8)
+            .map(|k| c / (2 * range + 1).pow(k) % (2 * range + 1) - range)
+            .collect();
+        if !in_domain(c[0], c[1], c[2], c[3]) && !in_domain(c[4], c[5], c[6], c[7]) {
+            continue;
+        }
+        forms += 1;
+        let c = [c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]];
+        for x in &inputs {
+            for y in &inputs {
+                assert_combine(c, *x, *y);
+            }
+        }
+    }
+    assert!(forms > 0);
+}
+
 fn reference_compare(mut n1: isize, mut d1: isize, mut n2: isize, mut d2: isize) -> Ordering {
     assert!(d1 != 0);
     assert!(d2 != 0);
@@ -135,6 +195,7 @@ fn reference_compare(mut n1: isize, mut d1: isize, mut n2: isize, mut d2: isize)
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn compare_combine(
     nxy: isize,
     nx: isize,
@@ -159,45 +220,58 @@ fn compare_combine(
         (2, 1),
     ];
 
-    for (xn, xd) in &inputs {
-        for (yn, yd) in &inputs {
-            let (rn, rd) = expected_combine(nxy, nx, ny, n, dxy, dx, dy, d, *xn, *xd, *yn, *yd);
-            if rd == 0 {
-                continue;
+    for x in &inputs {
+        for y in &inputs {
+            assert_combine([nxy, nx, ny, n, dxy, dx, dy, d], *x, *y);
+        }
+    }
+}
+
+/// Check a combination of x and y against the reference, unless it is undefined.
+fn assert_combine(c: [isize; 8], (xn, xd): (isize, isize), (yn, yd): (isize, isize)) {
+    let (rn, rd) = expected_combine(
+        c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7], xn, xd, yn, yd,
+    );
+    if rd == 0 {
+        return;
+    }
+    assert_eq!(
+        Number::compare(
+            Number::combine(
+                Number::ratio(xn, xd),
+                Number::ratio(yn, yd),
+                c[0],
+                c[1],
+                c[2],
+                c[3],
+                c[4],
+                c[5],
+                c[6],
+                c[7]
+            ),
+            Number::ratio(rn, rd)
+        ),
+        Ordering::Equal,
+        "error: {:?}  ({} {})  ({} {}) is not ({} {})",
+        c,
+        xn,
+        xd,
+        yn,
+        yd,
+        rn,
+        rd
+    );
+}
+
+/// Check a combination over all pairs of ratios with terms up to range.
+fn compare_combine_grid(c: [isize; 8], range: isize) {
+    for xn in -range..range + 1 {
+        for xd in 1..range + 1 {
+            for yn in -range..range + 1 {
+                for yd in 1..range + 1 {
+                    assert_combine(c, (xn, xd), (yn, yd));
+                }
             }
-            assert_eq!(
-                Number::compare(
-                    Number::combine(
-                        Number::ratio(*xn, *xd),
-                        Number::ratio(*yn, *yd),
-                        nxy,
-                        nx,
-                        ny,
-                        n,
-                        dxy,
-                        dx,
-                        dy,
-                        d
-                    ),
-                    Number::ratio(rn, rd)
-                ),
-                Ordering::Equal,
-                "error: {} {} {} {}  {} {} {} {}  ({} {})  ({} {}) is not ({} {})",
-                nxy,
-                nx,
-                ny,
-                n,
-                dxy,
-                dx,
-                dy,
-                d,
-                *xn,
-                *xd,
-                *yn,
-                *yd,
-                rn,
-                rd
-            );
         }
     }
 }
@@ -214,6 +288,7 @@ fn expected_homographic(
     div(add(mul((nx, 1), x), (n, 1)), add(mul((dx, 1), x), (d, 1)))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn expected_combine(
     nxy: isize,
     nx: isize,
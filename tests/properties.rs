@@ -0,0 +1,467 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+/*
+ * Randomized property tests against an exact rational oracle.
+ *
+ * Each property draws its arguments from a mix of small, large and near-overflow
+ * coefficients, and a failing case is shrunk before being reported.
+ * Set CLARITH_SEED to explore other cases or to reproduce a reported failure.
+ *
+ * Results are checked by decoding their symbols with unbounded integers,
+ * so the oracle never overflows. Overflow panics of the library on large coefficients
+ * are its known limit and only discard the case; with small coefficients they fail it,
+ * as does any other panic, such as a logic error.
+ */
+
+use clarith::protocol::{Primer, Reduction, Special};
+use clarith::{BigInt, Number, OVERFLOW};
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::env;
+use std::fmt;
+use std::panic;
+use std::sync::Once;
+
+/// Cases tried per property.
+const CASES: usize = 400;
+
+/// Seed used unless CLARITH_SEED is set.
+const SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Coefficients up to this magnitude must never make the library panic.
+const SMALL: usize = 16;
+
+/// Symbols decoded from a result before deciding it is not the expected rational.
+const SYMBOLS: usize = 1 << 16;
+
+#[test]
+fn property_ratio() {
+    check("ratio", 2, |c| {
+        let expected = match Rational::new(c[0].into(), c[1].into()) {
+            Some(expected) => expected,
+            None => return Outcome::Discard,
+        };
+        agree(Number::ratio(c[0], c[1]), &expected)
+    });
+}
+
+#[test]
+fn property_homographic() {
+    check("homographic", 6, |c| {
+        let x = match Rational::new(c[0].into(), c[1].into()) {
+            Some(x) => x,
+            None => return Outcome::Discard,
+        };
+        let expected = match x.homographic(c[2], c[3], c[4], c[5]) {
+            Some(expected) => expected,
+            None => return Outcome::Discard,
+        };
+        agree(
+            Number::homographic(Number::ratio(c[0], c[1]), c[2], c[3], c[4], c[5]),
+            &expected,
+        )
+    });
+}
+
+#[test]
+fn property_combine() {
+    check("combine", 12, |c| {
+        let (x, y) = match (
+            Rational::new(c[0].into(), c[1].into()),
+            Rational::new(c[2].into(), c[3].into()),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Outcome::Discard,
+        };
+        let expected = match x.combine(&y, &c[4..]) {
+            Some(expected) => expected,
+            None => return Outcome::Discard,
+        };
+        agree(
+            Number::combine(
+                Number::ratio(c[0], c[1]),
+                Number::ratio(c[2], c[3]),
+                c[4],
+                c[5],
+                c[6],
+                c[7],
+                c[8],
+                c[9],
+                c[10],
+                c[11],
+            ),
+            &expected,
+        )
+    });
+}
+
+#[test]
+fn property_consume() {
+    check("consume", 2, |c| {
+        let expected = match Rational::new(c[0].into(), c[1].into()) {
+            Some(expected) => expected,
+            None => return Outcome::Discard,
+        };
+        let (y, nx, n, dx, d) = Number::consume(Number::ratio(c[0], c[1]));
+        // Without a remaining Number, the coefficients alone give the value at one half.
+        let y = match y {
+            Some(y) => match value(y) {
+                Some(y) => y,
+                None => return Outcome::Fail("remaining number does not end".to_string()),
+            },
+            None => Rational::new(BigInt::from(1isize), BigInt::from(2isize)).unwrap(),
+        };
+        match y.homographic(nx, n, dx, d) {
+            Some(actual) if actual == expected => Outcome::Pass,
+            actual => Outcome::Fail(format!(
+                "consumed into ({} y + {}) / ({} y + {}) with y = {}, which is {:?}, not {}",
+                nx, n, dx, d, y, actual, expected
+            )),
+        }
+    });
+}
+
+#[test]
+fn property_compare() {
+    check("compare", 8, |c| {
+        let x = match Rational::new(c[0].into(), c[1].into()) {
+            Some(x) => x,
+            None => return Outcome::Discard,
+        };
+        let y = match Rational::new(c[2].into(), c[3].into())
+            .and_then(|y| y.homographic(c[4], c[5], c[6], c[7]))
+        {
+            Some(y) => y,
+            None => return Outcome::Discard,
+        };
+        let expected = x.cmp(&y);
+        let actual = Number::compare(
+            Number::ratio(c[0], c[1]),
+            Number::homographic(Number::ratio(c[2], c[3]), c[4], c[5], c[6], c[7]),
+        );
+        if actual == expected {
+            Outcome::Pass
+        } else {
+            Outcome::Fail(format!("compared {:?} for {} and {}", actual, x, y))
+        }
+    });
+}
+
+enum Outcome {
+    Pass,
+    /// The case does not satisfy the preconditions of the property.
+    Discard,
+    Fail(String),
+}
+
+/// Whether the value of x is expected.
+fn agree(x: Number, expected: &Rational) -> Outcome {
+    match value(x) {
+        Some(actual) if actual == *expected => Outcome::Pass,
+        Some(actual) => Outcome::Fail(format!("got {}, expected {}", actual, expected)),
+        None => Outcome::Fail(format!(
+            "got more than {} symbols, expected {}",
+            SYMBOLS, expected
+        )),
+    }
+}
+
+/// The exact value of x, if it has a limited number of symbols.
+fn value(x: Number) -> Option<Rational> {
+    let (primer, clog) = match x {
+        Number::Special(Special::NegOne) => {
+            return Rational::new(BigInt::from(-1isize), BigInt::from(1isize))
+        }
+        Number::Special(Special::Zero) => {
+            return Rational::new(BigInt::from(0isize), BigInt::from(1isize))
+        }
+        Number::Special(Special::PosOne) => {
+            return Rational::new(BigInt::from(1isize), BigInt::from(1isize))
+        }
+        Number::Other(primer, clog) => (primer, clog),
+    };
+    let reductions: Vec<Reduction> = clog.take(SYMBOLS + 1).collect();
+    if reductions.len() > SYMBOLS {
+        return None;
+    }
+    // The empty sequence is one half; undo each reduction from the last one.
+    let (mut num, mut den) = (BigInt::from(1isize), BigInt::from(2isize));
+    for reduction in reductions.iter().rev() {
+        match reduction {
+            Reduction::Amplify => den = &den * &BigInt::from(2isize),
+            Reduction::Uncover => {
+                let sum = &num + &den;
+                num = den;
+                den = sum;
+            }
+        }
+    }
+    match primer {
+        None => Rational::new(num, den),
+        Some(Primer::Turn) => Rational::new(den, num),
+        Some(Primer::Reflect) => Rational::new(-&num, den),
+        Some(Primer::Ground) => Rational::new(-&den, num),
+    }
+}
+
+/**
+ * Check property over random cases of arity coefficients.
+ *
+ * A failing case is shrunk towards zero, one coefficient at a time, before being reported.
+ */
+fn check<P: Fn(&[isize]) -> Outcome>(name: &str, arity: usize, property: P) {
+    let seed = env::var("CLARITH_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(SEED);
+    // Every property sees different cases for the same seed.
+    let mut rng = Rng(name.bytes().fold(seed, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    }) | 1);
+    let (mut passed, mut discarded) = (0, 0);
+    for _ in 0..CASES {
+        let case = rng.case(arity);
+        match run(&property, &case) {
+            Outcome::Pass => passed += 1,
+            Outcome::Discard => discarded += 1,
+            Outcome::Fail(message) => {
+                let (case, message) = shrink(&property, case, message);
+                panic!(
+                    "property {} fails for {:?} (seed {}): {}",
+                    name, case, seed, message
+                );
+            }
+        }
+    }
+    assert!(
+        passed >= CASES / 4,
+        "property {}: only {} of {} cases passed, {} discarded",
+        name,
+        passed,
+        CASES,
+        discarded
+    );
+}
+
+/// Run property on case, telling overflow of large coefficients from failure.
+fn run<P: Fn(&[isize]) -> Outcome>(property: &P, case: &[isize]) -> Outcome {
+    quiet_panics();
+    QUIET.with(|quiet| quiet.set(true));
+    let outcome = panic::catch_unwind(panic::AssertUnwindSafe(|| property(case)));
+    QUIET.with(|quiet| quiet.set(false));
+    let cause = match outcome {
+        Ok(outcome) => return outcome,
+        Err(cause) => cause,
+    };
+    let message = cause
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| cause.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    // The library raises OVERFLOW when coefficients exceed isize; anything else is a bug.
+    if message == OVERFLOW && case.iter().any(|c| c.unsigned_abs() > SMALL) {
+        Outcome::Discard
+    } else {
+        Outcome::Fail(format!("panic: {}", message))
+    }
+}
+
+/// A smallest failing neighbour of case, with its failure message.
+fn shrink<P: Fn(&[isize]) -> Outcome>(
+    property: &P,
+    mut case: Vec<isize>,
+    mut message: String,
+) -> (Vec<isize>, String) {
+    'progress: loop {
+        for i in 0..case.len() {
+            let c = case[i];
+            for candidate in [0, c / 2, c - c.signum()].iter() {
+                if *candidate == c {
+                    continue;
+                }
+                let mut smaller = case.clone();
+                smaller[i] = *candidate;
+                if let Outcome::Fail(m) = run(property, &smaller) {
+                    case = smaller;
+                    message = m;
+                    continue 'progress;
+                }
+            }
+        }
+        return (case, message);
+    }
+}
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Silence the panic messages of the library while a case runs, but not those of the tests.
+fn quiet_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let report = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !QUIET.with(|quiet| quiet.get()) {
+                report(info);
+            }
+        }));
+    });
+}
+
+/// Xorshift generator, good enough for picking test cases.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /**
+     * Coefficients of a case, all of about the same size:
+     * small, of a random number of bits, or small but for one close to overflow.
+     */
+    fn case(&mut self, arity: usize) -> Vec<isize> {
+        let r = self.next();
+        let bits = match r % 4 {
+            0 => 0,
+            _ => 5 + (r >> 2) % 58,
+        };
+        let mut case: Vec<isize> = (0..arity)
+            .map(|_| {
+                let c = self.next() as isize;
+                match bits {
+                    0 => c % (SMALL as isize + 1),
+                    _ => c >> (64 - bits),
+                }
+            })
+            .collect();
+        if r % 4 == 3 {
+            let near = match (r >> 8) % 4 {
+                0 => isize::MAX,
+                1 => isize::MIN + 1,
+                // A single product or sum of these overflows.
+                2 => isize::MAX / 2 + 1,
+                _ => isize::MIN / 2,
+            };
+            case[(r >> 16) as usize % arity] = near - near.signum() * ((r >> 24) % 3) as isize;
+            for c in case.iter_mut() {
+                if c.unsigned_abs() < isize::MAX.unsigned_abs() / 4 {
+                    *c %= SMALL as isize + 1;
+                }
+            }
+        }
+        case
+    }
+}
+
+/// Exact rational number, with positive denominator.
+#[derive(Clone, Debug)]
+struct Rational {
+    num: BigInt,
+    den: BigInt,
+}
+
+impl Rational {
+    fn new(num: BigInt, den: BigInt) -> Option<Rational> {
+        match den.signum().cmp(&0) {
+            Ordering::Equal => None,
+            Ordering::Less => Some(Rational {
+                num: -num,
+                den: -den,
+            }),
+            Ordering::Greater => Some(Rational { num, den }),
+        }
+    }
+
+    /// _(nx * self + n) / (dx * self + d)_, if defined.
+    fn homographic(&self, nx: isize, n: isize, dx: isize, d: isize) -> Option<Rational> {
+        let (nx, n, dx, d) = (nx.into(), n.into(), dx.into(), d.into());
+        Rational::new(
+            &(&nx * &self.num) + &(&n * &self.den),
+            &(&dx * &self.num) + &(&d * &self.den),
+        )
+    }
+
+    /// _(a * self * y + b * self + c * y + d) / (e * self * y + f * self + g * y + h)_, if defined.
+    fn combine(&self, y: &Rational, c: &[isize]) -> Option<Rational> {
+        let terms = [
+            &self.num * &y.num,
+            &self.num * &y.den,
+            &self.den * &y.num,
+            &self.den * &y.den,
+        ];
+        let polynomial = |c: &[isize]| {
+            c.iter()
+                .zip(terms.iter())
+                .fold(BigInt::from(0isize), |sum, (c, term)| {
+                    &sum + &(&BigInt::from(*c) * term)
+                })
+        };
+        Rational::new(polynomial(&c[..4]), polynomial(&c[4..]))
+    }
+}
+
+impl PartialEq for Rational {
+    fn eq(&self, other: &Rational) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Rational {}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Rational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Rational) -> Ordering {
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+#[test]
+fn oracle() {
+    let a = BigInt::from(1i128 << 64);
+    let b = BigInt::from(-12345678901234567890i128);
+    assert_eq!(
+        format!("{}", &a * &a),
+        "340282366920938463463374607431768211456"
+    );
+    assert_eq!(format!("{}", &a + &b), "6101065172474983726");
+    assert_eq!(format!("{}", &b + &(-&b)), "0");
+    assert!(&b * &b > a);
+    assert_eq!(
+        Rational::new(BigInt::from(2isize), BigInt::from(-4isize)),
+        Rational::new(BigInt::from(-1isize), BigInt::from(2isize))
+    );
+}
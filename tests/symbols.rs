@@ -0,0 +1,103 @@
+/*
+ * Copyright 2019 Rafael Lorandi <coolparadox@gmail.com>
+ *
+ * This file is part of clarith, a library for performing arithmetic
+ * in continued logarithm representation.
+ *
+ * clarith is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * clarith is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with clarith.  If not, see <http://www.gnu.org/licenses/>
+ */
+
+use clarith::protocol::{Primer, Reduction, Special, Symbol};
+use clarith::Number;
+use std::cmp::Ordering;
+
+#[test]
+fn test_clog_iterator() {
+    let (_, clog) = Number::ratio(1, 10).unwrap_other();
+    let reductions: Vec<Reduction> = clog.collect();
+    assert_eq!(
+        reductions,
+        vec![
+            Reduction::Amplify,
+            Reduction::Amplify,
+            Reduction::Amplify,
+            Reduction::Uncover,
+            Reduction::Amplify,
+        ]
+    );
+}
+
+#[test]
+fn test_clog_adapters() {
+    // 1 - 1/9 = 8/9 = 'UAA'
+    let (_, x) = Number::homographic(Number::ratio(1, 9), -1, 1, 0, 1).unwrap_other();
+    let (_, y) = Number::ratio(8, 9).unwrap_other();
+    assert!(x.eq(y));
+    let (_, x) = Number::ratio(8, 9).unwrap_other();
+    assert_eq!(x.count(), 3);
+}
+
+#[test]
+fn test_number_symbols() {
+    let symbols: Vec<Symbol> = Number::ratio(0, 1).into_iter().collect();
+    assert_eq!(symbols, vec![Symbol::Special(Special::Zero)]);
+    let symbols: Vec<Symbol> = Number::ratio(5, 1).into_iter().collect();
+    assert_eq!(
+        symbols,
+        vec![
+            Symbol::Primer(Primer::Turn),
+            Symbol::Reduction(Reduction::Amplify),
+            Symbol::Reduction(Reduction::Amplify),
+            Symbol::Reduction(Reduction::Uncover),
+            Symbol::Reduction(Reduction::Amplify),
+        ]
+    );
+    assert_eq!(Number::ratio(1, 2).into_iter().count(), 0);
+    let mut symbols = Number::ratio(-1, 4).into_iter();
+    assert_eq!(symbols.next(), Some(Symbol::Primer(Primer::Reflect)));
+    assert_eq!(symbols.next(), Some(Symbol::Reduction(Reduction::Amplify)));
+    assert_eq!(symbols.next(), None);
+    assert_eq!(symbols.next(), None);
+}
+
+#[test]
+fn test_from_symbols() {
+    // 1/10 = 'AAAUA'
+    let x = Number::from_symbols(None, "AAAUA".chars().map(reduction));
+    let (_, clog) = Number::ratio(2, 3).unwrap_other();
+    let symbols: Vec<Reduction> = clog.collect();
+    let len = symbols.len();
+    let y = Number::from_rational_symbols(None, symbols, len);
+    // 1/10 + 2/3 = 23/30
+    assert_eq!(
+        Number::compare(
+            Number::combine(x, y, 0, 1, 1, 0, 0, 0, 0, 1),
+            Number::ratio(23, 30)
+        ),
+        Ordering::Equal
+    );
+    let x = Number::from_symbols(Some(Primer::Turn), "AAUA".chars().map(reduction));
+    assert_eq!(
+        Number::compare(Number::homographic(x, 1, 0, 0, 2), Number::ratio(5, 2)),
+        Ordering::Equal
+    );
+}
+
+fn reduction(letter: char) -> Reduction {
+    match letter {
+        'A' => Reduction::Amplify,
+        'U' => Reduction::Uncover,
+        _ => panic!("invalid letter"),
+    }
+}